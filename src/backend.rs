@@ -0,0 +1,694 @@
+use crate::{
+    build_attach_command_args, build_create_window_args, build_hook_args, build_pane_args,
+    build_pane_command_args, build_rename_pane_args, build_select_layout_args, build_session_args,
+    Config, Hook, Layout, ScreenCommand, StartDirectory, TmuxCommand,
+};
+use std::path::Path;
+
+/// The operations used to start and configure a project's session, expressed
+/// as the commands which perform them.
+///
+/// Indices are absolute (i.e. they already account for base-index and
+/// pane-base-index) and the first window/pane of a session/window is assumed
+/// to be created implicitly. Operations a backend can't support should return
+/// no commands.
+pub trait MultiplexerBackend {
+    /// The type of command the terminal multiplexer is sent (e.g. TmuxCommand)
+    type Command;
+
+    fn create_session(
+        &self,
+        session_name: &str,
+        window_name: &Option<String>,
+        start_directory: &StartDirectory,
+    ) -> Vec<Self::Command>;
+
+    fn add_window(
+        &self,
+        session_name: &str,
+        window_index: usize,
+        window_name: &Option<String>,
+        start_directory: &StartDirectory,
+    ) -> Vec<Self::Command>;
+
+    /// Create the pane at pane_index by splitting the window.
+    fn split_pane(
+        &self,
+        session_name: &str,
+        window_index: usize,
+        pane_index: usize,
+    ) -> Vec<Self::Command>;
+
+    fn send_command(
+        &self,
+        session_name: &str,
+        window_index: usize,
+        pane_index: usize,
+        command: &str,
+    ) -> Vec<Self::Command>;
+
+    fn set_layout(
+        &self,
+        session_name: &str,
+        window_index: usize,
+        layout: &Layout,
+    ) -> Vec<Self::Command>;
+
+    fn attach(&self, session_name: &str) -> Vec<Self::Command>;
+
+    fn set_hook(&self, _hook: &Hook) -> Vec<Self::Command> {
+        vec![]
+    }
+
+    fn rename_pane(
+        &self,
+        _session_name: &str,
+        _window_index: usize,
+        _pane_index: usize,
+        _pane_name_user_option: &Option<String>,
+        _pane_name: &Option<String>,
+    ) -> Vec<Self::Command> {
+        vec![]
+    }
+}
+
+pub struct TmuxBackend;
+
+impl MultiplexerBackend for TmuxBackend {
+    type Command = TmuxCommand;
+
+    fn create_session(
+        &self,
+        session_name: &str,
+        window_name: &Option<String>,
+        start_directory: &StartDirectory,
+    ) -> Vec<TmuxCommand> {
+        vec![build_session_args(
+            session_name,
+            window_name.clone(),
+            start_directory,
+        )]
+    }
+
+    fn add_window(
+        &self,
+        session_name: &str,
+        window_index: usize,
+        window_name: &Option<String>,
+        start_directory: &StartDirectory,
+    ) -> Vec<TmuxCommand> {
+        vec![build_create_window_args(
+            session_name,
+            window_index,
+            window_name,
+            start_directory,
+        )]
+    }
+
+    fn split_pane(
+        &self,
+        session_name: &str,
+        window_index: usize,
+        _pane_index: usize,
+    ) -> Vec<TmuxCommand> {
+        build_pane_args(session_name, window_index)
+    }
+
+    fn send_command(
+        &self,
+        session_name: &str,
+        window_index: usize,
+        pane_index: usize,
+        command: &str,
+    ) -> Vec<TmuxCommand> {
+        vec![build_pane_command_args(
+            session_name,
+            &window_index,
+            &pane_index,
+            command,
+        )]
+    }
+
+    fn set_layout(
+        &self,
+        session_name: &str,
+        window_index: usize,
+        layout: &Layout,
+    ) -> Vec<TmuxCommand> {
+        vec![build_select_layout_args(
+            session_name,
+            &window_index,
+            layout,
+        )]
+    }
+
+    fn attach(&self, session_name: &str) -> Vec<TmuxCommand> {
+        vec![build_attach_command_args(session_name)]
+    }
+
+    fn set_hook(&self, hook: &Hook) -> Vec<TmuxCommand> {
+        vec![build_hook_args(hook)]
+    }
+
+    fn rename_pane(
+        &self,
+        session_name: &str,
+        window_index: usize,
+        pane_index: usize,
+        pane_name_user_option: &Option<String>,
+        pane_name: &Option<String>,
+    ) -> Vec<TmuxCommand> {
+        build_rename_pane_args(
+            session_name,
+            window_index,
+            pane_index,
+            pane_name_user_option,
+            pane_name,
+        )
+        .into_iter()
+        .collect()
+    }
+}
+
+/// Whether or not the terminal multiplexer is GNU screen (e.g. `screen` or
+/// `/usr/bin/screen`).
+pub fn is_screen(terminal_multiplexer: &str) -> bool {
+    Path::new(terminal_multiplexer)
+        .file_name()
+        .is_some_and(|file_name| file_name == "screen")
+}
+
+/// Drives GNU screen using `screen -S <session> -X <command>`.
+///
+/// Screen has no notion of panes, so each pane is run in its own screen
+/// window, numbered in the order the panes are created. Indices are expected
+/// to start at 0 (i.e. screen doesn't have base-index or pane-base-index).
+/// See unsupported_options and unsupported_layouts for the settings which
+/// can't be used.
+pub struct ScreenBackend {
+    // The number of the screen window which runs each project window's first
+    // pane, along with the title shared by all of its panes' screen windows.
+    windows: Vec<(usize, Option<String>)>,
+}
+
+impl ScreenBackend {
+    /// Build a backend for a session with the given windows, i.e. each
+    /// window's name and number of panes (and so screen windows).
+    pub fn new(windows: &[(Option<String>, usize)]) -> ScreenBackend {
+        let mut offset = 0;
+        let mut windows_ = vec![];
+        for (window_name, pane_count) in windows {
+            windows_.push((offset, window_name.clone()));
+            // Every window has at least one pane.
+            offset += (*pane_count).max(1);
+        }
+        ScreenBackend { windows: windows_ }
+    }
+
+    /// Describe the project settings which this backend ignores.
+    pub fn unsupported_options(config: &Config) -> Vec<String> {
+        let mut warnings = vec![];
+
+        if !config.hooks.is_empty() {
+            warnings.push(String::from("hooks: unsupported by screen"));
+        }
+        if config.tmux_options.is_some() {
+            warnings.push(String::from("tmux_options: unsupported by screen"));
+        }
+        if config.pane_name_user_option.is_some() {
+            warnings.push(String::from("pane_name_user_option: unsupported by screen"));
+        }
+
+        for (window_index, window) in config.windows.iter().enumerate() {
+            for (pane_index, pane) in window.panes.iter().enumerate() {
+                if pane.name.is_some() {
+                    warnings.push(format!(
+                        "windows[{}].panes[{}].name: unsupported by screen",
+                        window_index, pane_index
+                    ));
+                }
+            }
+        }
+
+        warnings
+    }
+
+    /// The paths of the layouts set in a project config (e.g.
+    /// `windows[1].layout`), none of which can be used with screen: its
+    /// regions belong to a display (i.e. an attached terminal) rather than to
+    /// a window, so they can't be set up in a detached session or per window.
+    pub fn unsupported_layouts(config: &Config) -> Vec<String> {
+        let mut paths = vec![];
+
+        if config.layout.is_some() {
+            paths.push(String::from("layout"));
+        }
+
+        for (window_index, window) in config.windows.iter().enumerate() {
+            if window.layout.is_some() {
+                paths.push(format!("windows[{}].layout", window_index));
+            }
+            for responsive_layout_index in 0..window.responsive_layouts.len() {
+                paths.push(format!(
+                    "windows[{}].responsive_layouts[{}]",
+                    window_index, responsive_layout_index
+                ));
+            }
+        }
+
+        paths
+    }
+
+    fn window_number(&self, window_index: usize, pane_index: usize) -> usize {
+        self.windows
+            .get(window_index)
+            .map_or(0, |(offset, _)| *offset)
+            + pane_index
+    }
+
+    fn window_title(&self, window_index: usize) -> Option<String> {
+        self.windows
+            .get(window_index)
+            .and_then(|(_, title)| title.clone())
+    }
+}
+
+fn build_chdir_commands(
+    session_name: &str,
+    start_directory: &StartDirectory,
+) -> Vec<ScreenCommand> {
+    start_directory
+        .iter()
+        .map(|start_directory_| ScreenCommand::Chdir {
+            session_name: String::from(session_name),
+            directory: start_directory_.clone(),
+        })
+        .collect()
+}
+
+impl MultiplexerBackend for ScreenBackend {
+    type Command = ScreenCommand;
+
+    fn create_session(
+        &self,
+        session_name: &str,
+        window_name: &Option<String>,
+        start_directory: &StartDirectory,
+    ) -> Vec<ScreenCommand> {
+        // The first window's shell starts in the current directory (its panes
+        // cd into their start_directory), so chdir only affects the windows
+        // created after it.
+        let mut commands = vec![ScreenCommand::NewSession {
+            session_name: String::from(session_name),
+            title: window_name.clone(),
+        }];
+        commands.extend(build_chdir_commands(session_name, start_directory));
+        commands
+    }
+
+    fn add_window(
+        &self,
+        session_name: &str,
+        window_index: usize,
+        window_name: &Option<String>,
+        start_directory: &StartDirectory,
+    ) -> Vec<ScreenCommand> {
+        let mut commands = build_chdir_commands(session_name, start_directory);
+        commands.push(ScreenCommand::NewWindow {
+            session_name: String::from(session_name),
+            window_number: self.window_number(window_index, 0),
+            title: window_name.clone(),
+        });
+        commands
+    }
+
+    fn split_pane(
+        &self,
+        session_name: &str,
+        window_index: usize,
+        pane_index: usize,
+    ) -> Vec<ScreenCommand> {
+        // Pane windows share the title of the project window they belong to
+        vec![ScreenCommand::NewWindow {
+            session_name: String::from(session_name),
+            window_number: self.window_number(window_index, pane_index),
+            title: self.window_title(window_index),
+        }]
+    }
+
+    fn send_command(
+        &self,
+        session_name: &str,
+        window_index: usize,
+        pane_index: usize,
+        command: &str,
+    ) -> Vec<ScreenCommand> {
+        vec![ScreenCommand::Stuff {
+            session_name: String::from(session_name),
+            window_number: self.window_number(window_index, pane_index),
+            command: String::from(command),
+        }]
+    }
+
+    fn set_layout(
+        &self,
+        _session_name: &str,
+        _window_index: usize,
+        _layout: &Layout,
+    ) -> Vec<ScreenCommand> {
+        // See unsupported_layouts
+        vec![]
+    }
+
+    fn attach(&self, session_name: &str) -> Vec<ScreenCommand> {
+        vec![ScreenCommand::Attach {
+            session_name: String::from(session_name),
+        }]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::build_planned_commands;
+    use crate::{ConditionEnv, Config, HookName, Pane, ScreenCommand, TmuxBaseIndices, Window};
+
+    fn build_backend_commands<B: MultiplexerBackend>(
+        backend: &B,
+        config: &Config,
+        base_indices: TmuxBaseIndices,
+    ) -> Vec<(B::Command, bool)> {
+        build_planned_commands(
+            backend,
+            config,
+            base_indices,
+            None,
+            &ConditionEnv::default(),
+        )
+        .into_iter()
+        .map(|planned_command| (planned_command.command, planned_command.wait))
+        .collect()
+    }
+
+    struct LabelBackend;
+
+    impl MultiplexerBackend for LabelBackend {
+        type Command = String;
+
+        fn create_session(
+            &self,
+            session_name: &str,
+            _window_name: &Option<String>,
+            _start_directory: &StartDirectory,
+        ) -> Vec<String> {
+            vec![format!("create {}", session_name)]
+        }
+
+        fn add_window(
+            &self,
+            _session_name: &str,
+            window_index: usize,
+            _window_name: &Option<String>,
+            _start_directory: &StartDirectory,
+        ) -> Vec<String> {
+            vec![format!("window {}", window_index)]
+        }
+
+        fn split_pane(
+            &self,
+            _session_name: &str,
+            window_index: usize,
+            pane_index: usize,
+        ) -> Vec<String> {
+            vec![format!("split {}.{}", window_index, pane_index)]
+        }
+
+        fn send_command(
+            &self,
+            _session_name: &str,
+            window_index: usize,
+            pane_index: usize,
+            command: &str,
+        ) -> Vec<String> {
+            vec![format!("send {}.{} {}", window_index, pane_index, command)]
+        }
+
+        fn set_layout(
+            &self,
+            _session_name: &str,
+            window_index: usize,
+            layout: &Layout,
+        ) -> Vec<String> {
+            vec![format!("layout {} {}", window_index, layout)]
+        }
+
+        fn attach(&self, session_name: &str) -> Vec<String> {
+            vec![format!("attach {}", session_name)]
+        }
+    }
+
+    #[test]
+    fn it_builds_commands_using_a_custom_backend() {
+        let config = Config {
+            attached: true,
+            hooks: vec![],
+            layout: Some(Layout::Tiled),
+            name: String::from("foo"),
+            pane_name_user_option: Some(String::from("pane_name")),
+            start_directory: Some(String::from("/foo")),
+            tmux_options: None,
+            windows: vec![
+                Window {
+                    panes: vec![Pane {
+                        commands: vec![String::from("vim")],
+                        name: Some(String::from("editor")),
+                        ..Pane::default()
+                    }],
+                    ..Window::default()
+                },
+                Window {
+                    panes: vec![
+                        Pane::default(),
+                        Pane {
+                            commands: vec![String::from("top")],
+                            start_directory: Some(String::from("/bar")),
+                            ..Pane::default()
+                        },
+                    ],
+                    ..Window::default()
+                },
+            ],
+            ..Config::default()
+        };
+        let base_indices = TmuxBaseIndices {
+            base_index: 1,
+            pane_base_index: 0,
+        };
+
+        let expected: Vec<(String, bool)> = vec![
+            ("create foo", false),
+            ("send 1.0 cd /foo", false),
+            ("send 1.0 vim", false),
+            ("layout 1 tiled", false),
+            ("window 2", false),
+            ("send 2.0 cd /foo", false),
+            ("split 2.1", false),
+            ("send 2.1 cd /bar", false),
+            ("send 2.1 top", false),
+            ("layout 2 tiled", false),
+            ("attach foo", true),
+        ]
+        .into_iter()
+        .map(|(command, wait)| (String::from(command), wait))
+        .collect();
+        let actual = build_backend_commands(&LabelBackend, &config, base_indices);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn it_detects_screen() {
+        assert!(is_screen("screen"));
+        assert!(is_screen("/usr/bin/screen"));
+        assert!(!is_screen("tmux"));
+        assert!(!is_screen("screen-wrapper"));
+    }
+
+    fn build_screen_backend(config: &Config) -> ScreenBackend {
+        ScreenBackend::new(
+            &config
+                .windows
+                .iter()
+                .map(|window| (window.name.clone(), window.panes.len()))
+                .collect::<Vec<(Option<String>, usize)>>(),
+        )
+    }
+
+    #[test]
+    fn it_builds_screen_commands() {
+        let build_pane = |command: &str| Pane {
+            commands: vec![String::from(command)],
+            ..Pane::default()
+        };
+        let config = Config {
+            attached: true,
+            name: String::from("foo"),
+            start_directory: Some(String::from("/foo")),
+            terminal_multiplexer: String::from("screen"),
+            windows: vec![
+                Window {
+                    name: Some(String::from("editor")),
+                    panes: vec![build_pane("vim")],
+                    ..Window::default()
+                },
+                Window {
+                    name: Some(String::from("logs")),
+                    panes: vec![build_pane("tail -f a.log"), build_pane("tail -f b.log")],
+                    start_directory: Some(String::from("/logs")),
+                    ..Window::default()
+                },
+                Window {
+                    panes: vec![build_pane("top")],
+                    ..Window::default()
+                },
+            ],
+            ..Config::default()
+        };
+        let base_indices = TmuxBaseIndices {
+            base_index: 0,
+            pane_base_index: 0,
+        };
+
+        let session_name = || String::from("foo");
+        let stuff = |window_number: usize, command: &str| {
+            (
+                ScreenCommand::Stuff {
+                    session_name: session_name(),
+                    window_number,
+                    command: String::from(command),
+                },
+                false,
+            )
+        };
+        let new_window = |window_number: usize, title: Option<&str>| {
+            (
+                ScreenCommand::NewWindow {
+                    session_name: session_name(),
+                    window_number,
+                    title: title.map(String::from),
+                },
+                false,
+            )
+        };
+        let chdir = |directory: &str| {
+            (
+                ScreenCommand::Chdir {
+                    session_name: session_name(),
+                    directory: String::from(directory),
+                },
+                false,
+            )
+        };
+        let expected = vec![
+            (
+                ScreenCommand::NewSession {
+                    session_name: session_name(),
+                    title: Some(String::from("editor")),
+                },
+                false,
+            ),
+            stuff(0, "cd /foo"),
+            stuff(0, "vim"),
+            chdir("/logs"),
+            new_window(1, Some("logs")),
+            stuff(1, "cd /logs"),
+            stuff(1, "tail -f a.log"),
+            new_window(2, Some("logs")),
+            stuff(2, "cd /logs"),
+            stuff(2, "tail -f b.log"),
+            chdir("/foo"),
+            new_window(3, None),
+            stuff(3, "cd /foo"),
+            stuff(3, "top"),
+            (
+                ScreenCommand::Attach {
+                    session_name: session_name(),
+                },
+                true,
+            ),
+        ];
+        let actual = build_backend_commands(&build_screen_backend(&config), &config, base_indices);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn it_doesnt_support_layouts_with_screen() {
+        let config = Config {
+            layout: Some(Layout::Tiled),
+            name: String::from("foo"),
+            terminal_multiplexer: String::from("screen"),
+            windows: vec![
+                Window {
+                    layout: Some(Layout::EvenHorizontal),
+                    panes: vec![Pane::default(), Pane::default()],
+                    ..Window::default()
+                },
+                Window {
+                    layout: Some(Layout::EvenVertical),
+                    panes: vec![Pane::default(), Pane::default()],
+                    ..Window::default()
+                },
+            ],
+            ..Config::default()
+        };
+        assert_eq!(
+            vec!["layout", "windows[0].layout", "windows[1].layout"],
+            ScreenBackend::unsupported_layouts(&config)
+        );
+
+        // Neither window's layout is sent to screen, which only gets the
+        // windows running their panes.
+        let base_indices = TmuxBaseIndices {
+            base_index: 0,
+            pane_base_index: 0,
+        };
+        let window_numbers: Vec<usize> =
+            build_backend_commands(&build_screen_backend(&config), &config, base_indices)
+                .into_iter()
+                .map(|(command, _)| match command {
+                    ScreenCommand::NewWindow { window_number, .. } => Ok(window_number),
+                    ScreenCommand::NewSession { .. } => Ok(0),
+                    command => Err(command),
+                })
+                .collect::<Result<_, _>>()
+                .unwrap();
+        assert_eq!(vec![0, 1, 2, 3], window_numbers);
+    }
+
+    #[test]
+    fn it_lists_options_unsupported_by_screen() {
+        let config = Config {
+            hooks: vec![Hook {
+                command: String::from("run-shell \"echo hi\""),
+                name: HookName::ClientAttached,
+            }],
+            layout: Some(Layout::Tiled),
+            name: String::from("foo"),
+            tmux_options: Some(String::from("-L foo")),
+            windows: vec![Window {
+                layout: Some(Layout::Custom(String::from("9959,213x59,0,0"))),
+                panes: vec![Pane {
+                    name: Some(String::from("editor")),
+                    ..Pane::default()
+                }],
+                ..Window::default()
+            }],
+            ..Config::default()
+        };
+        let expected = vec![
+            String::from("hooks: unsupported by screen"),
+            String::from("tmux_options: unsupported by screen"),
+            String::from("windows[0].panes[0].name: unsupported by screen"),
+        ];
+        assert_eq!(expected, ScreenBackend::unsupported_options(&config));
+    }
+}
@@ -0,0 +1,326 @@
+use crate::{
+    Condition, Config, ConfigDefaultValues, ForEach, Hook, Layout, Pane, ResponsiveLayout,
+    Severity, Window,
+};
+
+/// Builds a Config in code. Unset options have the same defaults as they do
+/// in project config files (e.g. `attached` is true and
+/// `terminal_multiplexer` is "tmux").
+///
+/// ```
+/// let config = rmuxinator::Config::builder("example")
+///     .start_directory("/tmp")
+///     .window(|window| {
+///         window
+///             .name("editor")
+///             .pane(|pane| pane.command("vim"))
+///             .pane(|pane| pane.command("git status"))
+///     })
+///     .build()
+///     .unwrap();
+/// assert_eq!(2, config.windows[0].panes.len());
+/// ```
+pub struct ConfigBuilder {
+    config: Config,
+}
+
+impl Config {
+    pub fn builder(name: &str) -> ConfigBuilder {
+        ConfigBuilder {
+            config: Config {
+                attached: ConfigDefaultValues::default_as_true(),
+                name: String::from(name),
+                terminal_multiplexer: ConfigDefaultValues::default_as_tmux(),
+                ..Config::default()
+            },
+        }
+    }
+}
+
+impl ConfigBuilder {
+    pub fn attached(mut self, attached: bool) -> ConfigBuilder {
+        self.config.attached = attached;
+        self
+    }
+
+    pub fn hook(mut self, hook: Hook) -> ConfigBuilder {
+        self.config.hooks.push(hook);
+        self
+    }
+
+    pub fn layout(mut self, layout: Layout) -> ConfigBuilder {
+        self.config.layout = Some(layout);
+        self
+    }
+
+    /// Add an entry to the `layouts` table, which can be referenced by name
+    /// (i.e. using `Layout::Custom(name)`).
+    pub fn named_layout(mut self, name: &str, layout: Layout) -> ConfigBuilder {
+        self.config.layouts.insert(String::from(name), layout);
+        self
+    }
+
+    pub fn pane_name_user_option(mut self, pane_name_user_option: &str) -> ConfigBuilder {
+        self.config.pane_name_user_option = Some(String::from(pane_name_user_option));
+        self
+    }
+
+    pub fn start_directory(mut self, start_directory: &str) -> ConfigBuilder {
+        self.config.start_directory = Some(String::from(start_directory));
+        self
+    }
+
+    pub fn terminal_multiplexer(mut self, terminal_multiplexer: &str) -> ConfigBuilder {
+        self.config.terminal_multiplexer = String::from(terminal_multiplexer);
+        self
+    }
+
+    pub fn tmux_options(mut self, tmux_options: &str) -> ConfigBuilder {
+        self.config.tmux_options = Some(String::from(tmux_options));
+        self
+    }
+
+    pub fn window<F>(mut self, build_window: F) -> ConfigBuilder
+    where
+        F: FnOnce(WindowBuilder) -> WindowBuilder,
+    {
+        let window_builder = build_window(WindowBuilder {
+            window: Window::default(),
+        });
+        self.config.windows.push(window_builder.window);
+        self
+    }
+
+    /// Resolve any references to named layouts (as
+    /// `Config::new_from_file_path` does) and validate the config. Only
+    /// errors are reported (i.e. warnings from `Config::validate` are
+    /// ignored).
+    pub fn build(self) -> Result<Config, String> {
+        let mut config = self.config;
+
+        config.resolve_named_layouts()?;
+
+        let errors: Vec<String> = config
+            .validate()
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .map(|diagnostic| diagnostic.to_string())
+            .collect();
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+}
+
+pub struct WindowBuilder {
+    window: Window,
+}
+
+impl WindowBuilder {
+    /// Repeat the window once per item (see `Config::expand_for_each`).
+    pub fn for_each(mut self, for_each: ForEach) -> WindowBuilder {
+        self.window.for_each = Some(for_each);
+        self
+    }
+
+    pub fn layout(mut self, layout: Layout) -> WindowBuilder {
+        self.window.layout = Some(layout);
+        self
+    }
+
+    pub fn name(mut self, name: &str) -> WindowBuilder {
+        self.window.name = Some(String::from(name));
+        self
+    }
+
+    pub fn pane<F>(mut self, build_pane: F) -> WindowBuilder
+    where
+        F: FnOnce(PaneBuilder) -> PaneBuilder,
+    {
+        let pane_builder = build_pane(PaneBuilder {
+            pane: Pane::default(),
+        });
+        self.window.panes.push(pane_builder.pane);
+        self
+    }
+
+    pub fn responsive_layout(mut self, responsive_layout: ResponsiveLayout) -> WindowBuilder {
+        self.window.responsive_layouts.push(responsive_layout);
+        self
+    }
+
+    pub fn start_directory(mut self, start_directory: &str) -> WindowBuilder {
+        self.window.start_directory = Some(String::from(start_directory));
+        self
+    }
+
+    /// Only create the window when `condition` holds.
+    pub fn when(mut self, condition: Condition) -> WindowBuilder {
+        self.window.when = Some(condition);
+        self
+    }
+}
+
+pub struct PaneBuilder {
+    pane: Pane,
+}
+
+impl PaneBuilder {
+    /// Add a command to run in the pane (after any previously added ones).
+    pub fn command(mut self, command: &str) -> PaneBuilder {
+        self.pane.commands.push(String::from(command));
+        self
+    }
+
+    /// Repeat the pane once per item (see `Config::expand_for_each`).
+    pub fn for_each(mut self, for_each: ForEach) -> PaneBuilder {
+        self.pane.for_each = Some(for_each);
+        self
+    }
+
+    pub fn name(mut self, name: &str) -> PaneBuilder {
+        self.pane.name = Some(String::from(name));
+        self
+    }
+
+    pub fn start_directory(mut self, start_directory: &str) -> PaneBuilder {
+        self.pane.start_directory = Some(String::from(start_directory));
+        self
+    }
+
+    /// Only create the pane when `condition` holds.
+    pub fn when(mut self, condition: Condition) -> PaneBuilder {
+        self.pane.when = Some(condition);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HookName;
+
+    #[test]
+    fn it_builds_a_config_with_serde_defaults() {
+        let config = Config::builder("foo").build().unwrap();
+        let decoded: Config = toml::from_str("name = \"foo\"").unwrap();
+        assert_eq!(decoded.attached, config.attached);
+        assert_eq!(decoded.terminal_multiplexer, config.terminal_multiplexer);
+        assert_eq!(decoded.to_toml(), config.to_toml());
+    }
+
+    #[test]
+    fn it_builds_windows_panes_and_hooks() {
+        let config = Config::builder("foo")
+            .attached(false)
+            .hook(Hook::new(HookName::ClientAttached, "run-shell 'echo hi'"))
+            .named_layout("wide", Layout::MainVertical)
+            .window(|window| {
+                window
+                    .name("editor")
+                    .layout(Layout::Custom(String::from("wide")))
+                    .pane(|pane| pane.command("vim").name("vim"))
+                    .pane(|pane| pane.start_directory("/tmp"))
+            })
+            .build()
+            .unwrap();
+
+        assert!(!config.attached);
+        assert_eq!("client-attached", config.hooks[0].name().to_string());
+        assert_eq!("run-shell 'echo hi'", config.hooks[0].command());
+        assert_eq!(Some(String::from("editor")), config.windows[0].name);
+        assert_eq!(
+            Some(String::from("main-vertical")),
+            config.windows[0]
+                .layout
+                .as_ref()
+                .map(|layout| layout.to_string())
+        );
+        assert_eq!(vec!["vim"], config.windows[0].panes[0].commands);
+        assert_eq!(
+            Some(String::from("/tmp")),
+            config.windows[0].panes[1].start_directory
+        );
+    }
+
+    #[test]
+    fn it_builds_repeated_windows_and_panes() {
+        let mut config = Config::builder("foo")
+            .window(|window| {
+                window
+                    .name("{{ item }}")
+                    .for_each(ForEach::Items(vec![
+                        String::from("api"),
+                        String::from("web"),
+                    ]))
+                    .pane(|pane| {
+                        pane.command("ssh {{ item }}")
+                            .for_each(ForEach::Items(vec![String::from("db-1")]))
+                    })
+            })
+            .build()
+            .unwrap();
+        config.expand_for_each();
+
+        let names: Vec<Option<String>> = config
+            .windows
+            .iter()
+            .map(|window| window.name.clone())
+            .collect();
+        assert_eq!(
+            vec![Some(String::from("api")), Some(String::from("web"))],
+            names
+        );
+        assert_eq!(vec!["ssh db-1"], config.windows[1].panes[0].commands);
+    }
+
+    #[test]
+    fn it_builds_conditional_windows_and_panes() {
+        let docker = Condition {
+            command: Some(String::from("docker")),
+            ..Condition::default()
+        };
+        let macos = Condition {
+            os: Some(String::from("macos")),
+            ..Condition::default()
+        };
+        let config = Config::builder("foo")
+            .window(|window| {
+                window
+                    .when(docker.clone())
+                    .pane(|pane| pane.command("brew upgrade").when(macos.clone()))
+            })
+            .build()
+            .unwrap();
+
+        assert_eq!(Some(docker), config.windows[0].when);
+        assert_eq!(Some(macos), config.windows[0].panes[0].when);
+    }
+
+    #[test]
+    fn it_validates_the_name() {
+        assert!(Config::builder("").build().is_err());
+        assert_eq!(
+            Err(String::from(
+                "name: session name \"foo.bar\" must not contain ':' or '.'"
+            )),
+            Config::builder("foo.bar").build().map(|config| config.name)
+        );
+    }
+
+    #[test]
+    fn it_validates_layout_references() {
+        let actual = Config::builder("foo")
+            .window(|window| window.layout(Layout::Custom(String::from("wide"))))
+            .build()
+            .map(|config| config.name);
+        assert_eq!(
+            Err(String::from(
+                "Unknown layout \"wide\" referenced by windows[0].layout (no named layouts are defined)"
+            )),
+            actual
+        );
+    }
+}
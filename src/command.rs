@@ -0,0 +1,429 @@
+use serde::Serialize;
+
+/// Which options table `set-option` modifies.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OptionScope {
+    Global,
+    Session,
+    Window,
+    Pane,
+}
+
+/// A command which is sent to tmux.
+///
+/// Targets use tmux's `session:window.pane` syntax with absolute (i.e.
+/// base-index and pane-base-index aware) indices.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case", tag = "type")]
+pub enum TmuxCommand {
+    NewSession {
+        session_name: String,
+        window_name: Option<String>,
+        start_directory: Option<String>,
+    },
+    NewWindow {
+        target: String,
+        window_name: Option<String>,
+        start_directory: Option<String>,
+    },
+    SplitWindow {
+        target: String,
+    },
+    /// Type a command into a pane and press Enter
+    SendKeys {
+        target: String,
+        command: String,
+    },
+    SelectLayout {
+        target: String,
+        layout: String,
+    },
+    /// Append a command to a hook
+    SetHook {
+        hook_name: String,
+        command: String,
+    },
+    SetOption {
+        scope: OptionScope,
+        target: Option<String>,
+        option: String,
+        value: String,
+    },
+    /// Attach to a session or, if already inside of tmux, switch to it
+    Attach {
+        session_name: String,
+        switch_client: bool,
+    },
+}
+
+fn push_option(args: &mut Vec<String>, flag: &str, value: &Option<String>) {
+    if let Some(value_) = value {
+        args.push(String::from(flag));
+        args.push(value_.clone());
+    }
+}
+
+impl TmuxCommand {
+    /// The arguments to pass to the terminal multiplexer (i.e. excluding the
+    /// executable and any tmux_options).
+    pub fn to_argv(&self) -> Vec<String> {
+        match self {
+            TmuxCommand::NewSession {
+                session_name,
+                window_name,
+                start_directory,
+            } => {
+                let mut args = vec![
+                    String::from("new-session"),
+                    String::from("-d"),
+                    String::from("-s"),
+                    session_name.clone(),
+                ];
+                push_option(&mut args, "-n", window_name);
+                push_option(&mut args, "-c", start_directory);
+                args
+            }
+            TmuxCommand::NewWindow {
+                target,
+                window_name,
+                start_directory,
+            } => {
+                let mut args = vec![
+                    String::from("new-window"),
+                    String::from("-t"),
+                    target.clone(),
+                ];
+                push_option(&mut args, "-n", window_name);
+                push_option(&mut args, "-c", start_directory);
+                args
+            }
+            TmuxCommand::SplitWindow { target } => vec![
+                String::from("split-window"),
+                String::from("-t"),
+                target.clone(),
+            ],
+            TmuxCommand::SendKeys { target, command } => vec![
+                String::from("send-keys"),
+                String::from("-t"),
+                target.clone(),
+                command.clone(),
+                String::from("Enter"),
+            ],
+            TmuxCommand::SelectLayout { target, layout } => vec![
+                String::from("select-layout"),
+                String::from("-t"),
+                target.clone(),
+                layout.clone(),
+            ],
+            TmuxCommand::SetHook { hook_name, command } => vec![
+                String::from("set-hook"),
+                String::from("-a"),
+                hook_name.clone(),
+                command.clone(),
+            ],
+            TmuxCommand::SetOption {
+                scope,
+                target,
+                option,
+                value,
+            } => {
+                let mut args = vec![String::from("set-option")];
+                match scope {
+                    OptionScope::Global => args.push(String::from("-g")),
+                    OptionScope::Session => (),
+                    OptionScope::Window => args.push(String::from("-w")),
+                    OptionScope::Pane => args.push(String::from("-p")),
+                }
+                push_option(&mut args, "-t", target);
+                args.push(option.clone());
+                args.push(value.clone());
+                args
+            }
+            TmuxCommand::Attach {
+                session_name,
+                switch_client,
+            } => {
+                let session_op = if *switch_client {
+                    "switch-client"
+                } else {
+                    "attach-session"
+                };
+                vec![
+                    String::from("-u"),
+                    String::from(session_op),
+                    String::from("-t"),
+                    session_name.clone(),
+                ]
+            }
+        }
+    }
+
+    /// Render as a line of shell script which runs the command using the
+    /// given terminal multiplexer.
+    pub fn to_shell(&self, terminal_multiplexer: &str) -> String {
+        build_shell_line(terminal_multiplexer, &self.to_argv())
+    }
+
+    /// Render as a line of a tmux command file (i.e. one which can be loaded
+    /// using `tmux source-file`).
+    ///
+    /// Args are quoted as they are for a shell: tmux's parser also treats
+    /// single quoted strings literally (i.e. without `$`, `~`, `#{}` or escape
+    /// expansion) and joins them with adjacent text, so `'\''` works the same.
+    pub fn to_tmux_script(&self) -> String {
+        match self {
+            // Command files are run by a server, which can only switch
+            // (existing) clients and doesn't understand client flags like -u.
+            TmuxCommand::Attach { session_name, .. } => {
+                format!("switch-client -t {}", shell_quote(session_name))
+            }
+            _ => self
+                .to_argv()
+                .iter()
+                .map(|arg| shell_quote(arg))
+                .collect::<Vec<String>>()
+                .join(" "),
+        }
+    }
+}
+
+/// A command which is sent to GNU screen.
+///
+/// Screen has no panes, so each of a project's panes is run in a screen
+/// window of its own. Windows are addressed by their (zero-based) number.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case", tag = "type")]
+pub enum ScreenCommand {
+    /// Start a detached session, which creates window 0
+    NewSession {
+        session_name: String,
+        title: Option<String>,
+    },
+    /// Change the directory which windows created afterwards start in
+    Chdir {
+        session_name: String,
+        directory: String,
+    },
+    NewWindow {
+        session_name: String,
+        window_number: usize,
+        title: Option<String>,
+    },
+    /// Type a command into a window and press Enter
+    Stuff {
+        session_name: String,
+        window_number: usize,
+        command: String,
+    },
+    Attach {
+        session_name: String,
+    },
+}
+
+fn build_screen_command_args(
+    session_name: &str,
+    window_number: Option<usize>,
+    command: &str,
+) -> Vec<String> {
+    let mut args = vec![String::from("-S"), String::from(session_name)];
+    if let Some(window_number_) = window_number {
+        args.push(String::from("-p"));
+        args.push(window_number_.to_string());
+    }
+    args.push(String::from("-X"));
+    args.push(String::from(command));
+    args
+}
+
+impl ScreenCommand {
+    /// The arguments to pass to screen.
+    pub fn to_argv(&self) -> Vec<String> {
+        match self {
+            ScreenCommand::NewSession {
+                session_name,
+                title,
+            } => {
+                let mut args = vec![String::from("-dmS"), session_name.clone()];
+                push_option(&mut args, "-t", title);
+                args
+            }
+            ScreenCommand::Chdir {
+                session_name,
+                directory,
+            } => {
+                let mut args = build_screen_command_args(session_name, None, "chdir");
+                args.push(directory.clone());
+                args
+            }
+            ScreenCommand::NewWindow {
+                session_name,
+                window_number,
+                title,
+            } => {
+                let mut args = build_screen_command_args(session_name, None, "screen");
+                push_option(&mut args, "-t", title);
+                args.push(window_number.to_string());
+                args
+            }
+            ScreenCommand::Stuff {
+                session_name,
+                window_number,
+                command,
+            } => {
+                let mut args =
+                    build_screen_command_args(session_name, Some(*window_number), "stuff");
+                // ^M is screen's notation for a carriage return (i.e. Enter)
+                args.push(format!("{}^M", command));
+                args
+            }
+            ScreenCommand::Attach { session_name } => {
+                vec![String::from("-r"), session_name.clone()]
+            }
+        }
+    }
+
+    /// Render as a line of shell script which runs the command using the
+    /// given screen executable.
+    pub fn to_shell(&self, terminal_multiplexer: &str) -> String {
+        build_shell_line(terminal_multiplexer, &self.to_argv())
+    }
+}
+
+/// A command for whichever terminal multiplexer a project uses, as found in a
+/// Plan.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum MultiplexerCommand {
+    Tmux(TmuxCommand),
+    Screen(ScreenCommand),
+}
+
+impl MultiplexerCommand {
+    /// See TmuxCommand::to_argv and ScreenCommand::to_argv.
+    pub fn to_argv(&self) -> Vec<String> {
+        match self {
+            MultiplexerCommand::Tmux(command) => command.to_argv(),
+            MultiplexerCommand::Screen(command) => command.to_argv(),
+        }
+    }
+
+    /// See TmuxCommand::to_shell and ScreenCommand::to_shell.
+    pub fn to_shell(&self, terminal_multiplexer: &str) -> String {
+        build_shell_line(terminal_multiplexer, &self.to_argv())
+    }
+}
+
+impl From<TmuxCommand> for MultiplexerCommand {
+    fn from(command: TmuxCommand) -> Self {
+        MultiplexerCommand::Tmux(command)
+    }
+}
+
+impl From<ScreenCommand> for MultiplexerCommand {
+    fn from(command: ScreenCommand) -> Self {
+        MultiplexerCommand::Screen(command)
+    }
+}
+
+pub fn build_shell_line(terminal_multiplexer: &str, args: &[String]) -> String {
+    std::iter::once(shell_quote(terminal_multiplexer))
+        .chain(args.iter().map(|arg| shell_quote(arg)))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+pub fn shell_quote(arg: &str) -> String {
+    let is_safe = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "%+,-./:=@_".contains(c));
+    if is_safe {
+        String::from(arg)
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_renders_set_option_argv_for_each_scope() {
+        let build = |scope: OptionScope| TmuxCommand::SetOption {
+            scope,
+            target: Some(String::from("foo:1")),
+            option: String::from("@bar"),
+            value: String::from("baz"),
+        };
+        assert_eq!(
+            vec!["set-option", "-g", "-t", "foo:1", "@bar", "baz"],
+            build(OptionScope::Global).to_argv()
+        );
+        assert_eq!(
+            vec!["set-option", "-t", "foo:1", "@bar", "baz"],
+            build(OptionScope::Session).to_argv()
+        );
+        assert_eq!(
+            vec!["set-option", "-w", "-t", "foo:1", "@bar", "baz"],
+            build(OptionScope::Window).to_argv()
+        );
+        assert_eq!(
+            vec!["set-option", "-p", "-t", "foo:1", "@bar", "baz"],
+            build(OptionScope::Pane).to_argv()
+        );
+    }
+
+    #[test]
+    fn it_renders_shell_quoted_text() {
+        let command = TmuxCommand::SendKeys {
+            target: String::from("foo:1.0"),
+            command: String::from("echo 'hi' $HOME"),
+        };
+        assert_eq!(
+            "tmux send-keys -t foo:1.0 'echo '\\''hi'\\'' $HOME' Enter",
+            command.to_shell("tmux")
+        );
+    }
+
+    #[test]
+    fn it_renders_tmux_script_lines() {
+        let command = TmuxCommand::NewWindow {
+            target: String::from("foo:1"),
+            window_name: Some(String::from("my window")),
+            start_directory: None,
+        };
+        assert_eq!(
+            "new-window -t foo:1 -n 'my window'",
+            command.to_tmux_script()
+        );
+    }
+
+    #[test]
+    fn it_renders_attach_as_switch_client_in_tmux_scripts() {
+        let command = TmuxCommand::Attach {
+            session_name: String::from("foo"),
+            switch_client: false,
+        };
+        assert_eq!(vec!["-u", "attach-session", "-t", "foo"], command.to_argv());
+        assert_eq!("switch-client -t foo", command.to_tmux_script());
+    }
+
+    #[test]
+    fn it_renders_screen_commands() {
+        let command = ScreenCommand::Stuff {
+            session_name: String::from("foo"),
+            window_number: 2,
+            command: String::from("tail -f a.log"),
+        };
+        assert_eq!(
+            vec!["-S", "foo", "-p", "2", "-X", "stuff", "tail -f a.log^M"],
+            command.to_argv()
+        );
+        assert_eq!(
+            "screen -S foo -p 2 -X stuff 'tail -f a.log^M'",
+            MultiplexerCommand::from(command).to_shell("screen")
+        );
+    }
+}
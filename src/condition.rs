@@ -0,0 +1,292 @@
+use crate::glob::matches_glob;
+use crate::{expand_home, Config, EnvProvider};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::Command;
+
+/// Conditions which must all hold for a window or pane to be created, e.g.
+/// `when = { command = "docker" }`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Condition {
+    /// An executable which must be on $PATH
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// An environment variable which must be set (e.g. `SSH_AUTH_SOCK`) or
+    /// have a value (e.g. `DEPLOY_ENV=staging`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<String>,
+    /// A file or directory which must exist
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// The machine's hostname, which may contain `*` and `?` wildcards
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    /// The operating system (e.g. `linux` or `macos`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub os: Option<String>,
+}
+
+/// What `when` conditions are checked against when planning (see PlanEnv).
+/// `command` and `file` conditions check this machine's filesystem, using
+/// `vars` to find $PATH and $HOME.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConditionEnv {
+    /// Environment variables
+    pub vars: HashMap<String, String>,
+    pub hostname: Option<String>,
+    /// The operating system (e.g. `linux` or `macos`)
+    pub os: String,
+}
+
+impl EnvProvider for ConditionEnv {
+    fn get_var(&self, key: &str) -> Option<String> {
+        self.vars.get(key).cloned()
+    }
+}
+
+fn system_hostname() -> Option<String> {
+    fs::read_to_string("/proc/sys/kernel/hostname")
+        .ok()
+        .or_else(|| {
+            Command::new("hostname")
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .and_then(|output| String::from_utf8(output.stdout).ok())
+        })
+        .map(|hostname| String::from(hostname.trim()))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    path.metadata()
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+// There's no executable bit to check elsewhere.
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+fn is_on_path(command: &str, env: &dyn EnvProvider) -> bool {
+    if command.contains('/') {
+        return is_executable(&expand_home(command, env));
+    }
+    env.get_var("PATH")
+        .map(|path| std::env::split_paths(&path).any(|dir| is_executable(&dir.join(command))))
+        .unwrap_or(false)
+}
+
+impl Condition {
+    fn holds(&self, condition_env: &ConditionEnv) -> bool {
+        let env = condition_env;
+        self.command
+            .as_ref()
+            .is_none_or(|command| is_on_path(command, env))
+            && self
+                .env
+                .as_ref()
+                .is_none_or(|variable| match variable.split_once('=') {
+                    Some((name, value)) => env.get_var(name).as_deref() == Some(value),
+                    None => env.get_var(variable).is_some(),
+                })
+            && self
+                .file
+                .as_ref()
+                .is_none_or(|file| expand_home(file, env).exists())
+            && self.hostname.as_ref().is_none_or(|pattern| {
+                condition_env
+                    .hostname
+                    .as_ref()
+                    .is_some_and(|hostname| matches_glob(pattern, hostname))
+            })
+            && self
+                .os
+                .as_ref()
+                .is_none_or(|os| os.eq_ignore_ascii_case(&condition_env.os))
+    }
+}
+
+impl ConditionEnv {
+    /// This machine's environment, hostname and operating system.
+    pub fn system() -> ConditionEnv {
+        ConditionEnv {
+            // Variables which aren't valid unicode can't be matched by an
+            // `env` condition anyway.
+            vars: std::env::vars_os()
+                .filter_map(|(key, value)| {
+                    Some((key.into_string().ok()?, value.into_string().ok()?))
+                })
+                .collect(),
+            hostname: system_hostname(),
+            os: String::from(std::env::consts::OS),
+        }
+    }
+
+    /// Whether a window or pane with the given `when` conditions is created.
+    pub fn holds(&self, condition: &Option<Condition>) -> bool {
+        condition
+            .as_ref()
+            .is_none_or(|condition_| condition_.holds(self))
+    }
+}
+
+impl Config {
+    /// Remove the windows and panes whose `when` conditions don't hold on
+    /// this machine.
+    ///
+    /// NOTE: Plans skip these windows and panes (without leaving gaps in the
+    /// indices) by themselves, so this is only needed in order to use the
+    /// config in some other way (e.g. to export it as a zellij layout).
+    pub fn apply_conditions(&mut self) {
+        self.apply_conditions_with(&ConditionEnv::system());
+    }
+
+    fn apply_conditions_with(&mut self, condition_env: &ConditionEnv) {
+        self.windows
+            .retain(|window| condition_env.holds(&window.when));
+        for window in self.windows.iter_mut() {
+            window.panes.retain(|pane| condition_env.holds(&pane.when));
+            // The conditions have been evaluated, so they no longer apply.
+            window.when = None;
+            for pane in window.panes.iter_mut() {
+                pane.when = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Pane, Window};
+    use tempfile::TempDir;
+
+    fn build_condition_env(vars: HashMap<String, String>) -> ConditionEnv {
+        ConditionEnv {
+            vars,
+            hostname: Some(String::from("work-laptop")),
+            os: String::from("linux"),
+        }
+    }
+
+    #[test]
+    fn it_checks_each_kind_of_condition() {
+        let bin_dir = TempDir::new().unwrap();
+        let docker_path = bin_dir.path().join("docker");
+        fs::write(&docker_path, "").unwrap();
+        #[cfg(unix)]
+        fs::set_permissions(&docker_path, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::write(bin_dir.path().join("not-executable"), "").unwrap();
+        let condition_env = build_condition_env(HashMap::from([
+            (String::from("DEPLOY_ENV"), String::from("staging")),
+            (String::from("HOME"), bin_dir.path().display().to_string()),
+            (
+                String::from("PATH"),
+                std::env::join_paths([Path::new("/nonexistent"), bin_dir.path()])
+                    .unwrap()
+                    .into_string()
+                    .unwrap(),
+            ),
+        ]));
+        let check = |condition: Condition| condition.holds(&condition_env);
+
+        assert!(check(Condition::default()));
+        assert!(check(Condition {
+            command: Some(String::from("docker")),
+            ..Condition::default()
+        }));
+        #[cfg(unix)]
+        assert!(!check(Condition {
+            command: Some(String::from("not-executable")),
+            ..Condition::default()
+        }));
+        assert!(check(Condition {
+            env: Some(String::from("DEPLOY_ENV")),
+            ..Condition::default()
+        }));
+        assert!(!check(Condition {
+            env: Some(String::from("DEPLOY_ENV=production")),
+            ..Condition::default()
+        }));
+        assert!(check(Condition {
+            file: Some(String::from("~/docker")),
+            ..Condition::default()
+        }));
+        assert!(!check(Condition {
+            file: Some(String::from("~/docker-compose.yml")),
+            ..Condition::default()
+        }));
+        assert!(check(Condition {
+            hostname: Some(String::from("work-*")),
+            ..Condition::default()
+        }));
+        assert!(!check(Condition {
+            hostname: Some(String::from("work")),
+            ..Condition::default()
+        }));
+        assert!(check(Condition {
+            os: Some(String::from("Linux")),
+            ..Condition::default()
+        }));
+        // All conditions must hold.
+        assert!(!check(Condition {
+            command: Some(String::from("docker")),
+            os: Some(String::from("macos")),
+            ..Condition::default()
+        }));
+    }
+
+    #[test]
+    fn it_removes_windows_and_panes_whose_conditions_dont_hold() {
+        let on_macos = || {
+            Some(Condition {
+                os: Some(String::from("macos")),
+                ..Condition::default()
+            })
+        };
+        let mut config = Config {
+            windows: vec![
+                Window {
+                    name: Some(String::from("logs")),
+                    when: on_macos(),
+                    ..Window::default()
+                },
+                Window {
+                    name: Some(String::from("editor")),
+                    panes: vec![
+                        Pane {
+                            commands: vec![String::from("open .")],
+                            when: on_macos(),
+                            ..Pane::default()
+                        },
+                        Pane {
+                            commands: vec![String::from("vim")],
+                            when: Some(Condition {
+                                os: Some(String::from("linux")),
+                                ..Condition::default()
+                            }),
+                            ..Pane::default()
+                        },
+                    ],
+                    ..Window::default()
+                },
+            ],
+            ..Config::default()
+        };
+        config.apply_conditions_with(&build_condition_env(HashMap::new()));
+
+        assert_eq!(1, config.windows.len());
+        assert_eq!(Some(String::from("editor")), config.windows[0].name);
+        assert_eq!(1, config.windows[0].panes.len());
+        assert_eq!(vec!["vim"], config.windows[0].panes[0].commands);
+        assert_eq!(None, config.windows[0].panes[0].when);
+    }
+}
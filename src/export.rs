@@ -0,0 +1,807 @@
+use crate::command::shell_quote;
+use crate::plan::{expand_for_planning, select_windows};
+use crate::{
+    build_commands_with_tmux_options_prefix, convert_config_to_tmux_commands, write_output,
+    ConditionEnv, Config, ConfigFormat, MultiplexerCommand, Pane, PlanEnv, TmuxBaseIndices,
+    TmuxCommand, Window,
+};
+use std::error::Error;
+
+// Conversion of project configs into formats which can be used without
+// rmuxinator.
+
+fn build_shell_target(session_name: &str, target: &str) -> Option<String> {
+    // Targets are built using zero-based indices and then offset by the
+    // base-index/pane-base-index of whichever tmux server runs the script.
+    let indices = target.strip_prefix(&format!("{}:", session_name))?;
+    let mut parts = indices.splitn(2, '.');
+    let window_index = parts.next()?.parse::<usize>().ok()?;
+    let window_target = format!("$((base_index + {}))", window_index);
+    let indices_ = match parts.next() {
+        Some(pane_index) => format!(
+            "{}.$((pane_base_index + {}))",
+            window_target,
+            pane_index.parse::<usize>().ok()?
+        ),
+        None => window_target,
+    };
+    Some(format!("{}:\"{}\"", shell_quote(session_name), indices_))
+}
+
+fn build_shell_command(
+    terminal_multiplexer: &str,
+    session_name: &str,
+    command: &[String],
+) -> String {
+    let mut parts = vec![shell_quote(terminal_multiplexer)];
+    let mut is_target = false;
+    for arg in command {
+        let part = if is_target {
+            build_shell_target(session_name, arg).unwrap_or_else(|| shell_quote(arg))
+        } else {
+            shell_quote(arg)
+        };
+        is_target = arg == "-t";
+        parts.push(part);
+    }
+    parts.join(" ")
+}
+
+fn build_commands_with_config_tmux_options(
+    config: &Config,
+    commands: Vec<Vec<String>>,
+) -> Vec<Vec<String>> {
+    let commands_ = commands
+        .into_iter()
+        .map(|command| (command, false))
+        .collect();
+    let commands__ = match config.tmux_options.clone() {
+        Some(tmux_options) => build_commands_with_tmux_options_prefix(tmux_options, commands_),
+        None => commands_,
+    };
+    commands__.into_iter().map(|(command, _)| command).collect()
+}
+
+fn build_shell_commands(config: &Config, commands: Vec<Vec<String>>) -> Vec<String> {
+    commands
+        .iter()
+        .map(|command| build_shell_command(&config.terminal_multiplexer, &config.name, command))
+        .collect()
+}
+
+/// Render a project config as a standalone POSIX shell script which creates
+/// (or attaches to an existing) session without needing rmuxinator. Windows
+/// and panes whose `when` conditions don't hold on this machine are left out
+/// (i.e. conditions aren't checked when the script is run).
+pub fn export_shell_script(config: &Config) -> String {
+    let base_indices = TmuxBaseIndices {
+        base_index: 0,
+        pane_base_index: 0,
+    };
+    // NOTE: The attach command (i.e. the only one which waits) is dropped
+    // because the script needs to decide whether to attach or switch clients
+    // when it's run rather than when it's generated. Responsive layouts which
+    // depend on the terminal's size can't be selected for the same reason.
+    let session_commands = build_shell_commands(
+        config,
+        convert_config_to_tmux_commands(config, base_indices, None)
+            .into_iter()
+            .filter(|(_, wait)| !wait)
+            .map(|(command, _)| command)
+            .collect(),
+    );
+
+    let helper_commands = build_shell_commands(
+        config,
+        build_commands_with_config_tmux_options(
+            config,
+            vec![
+                vec![
+                    String::from("has-session"),
+                    String::from("-t"),
+                    format!("={}", config.name),
+                ],
+                vec![
+                    String::from("start-server"),
+                    String::from(";"),
+                    String::from("show-option"),
+                    String::from("-gv"),
+                    String::from("base-index"),
+                ],
+                vec![
+                    String::from("start-server"),
+                    String::from(";"),
+                    String::from("show-window-option"),
+                    String::from("-gv"),
+                    String::from("pane-base-index"),
+                ],
+                vec![
+                    String::from("-u"),
+                    String::from("switch-client"),
+                    String::from("-t"),
+                    config.name.clone(),
+                ],
+                vec![
+                    String::from("-u"),
+                    String::from("attach-session"),
+                    String::from("-t"),
+                    config.name.clone(),
+                ],
+            ],
+        ),
+    );
+    let (has_session, base_index, pane_base_index, switch_client, attach_session) = (
+        &helper_commands[0],
+        &helper_commands[1],
+        &helper_commands[2],
+        &helper_commands[3],
+        &helper_commands[4],
+    );
+
+    let mut lines = vec![
+        String::from("#!/bin/sh"),
+        format!(
+            "# Generated by rmuxinator from the {} project config.",
+            shell_quote(&config.name)
+        ),
+        String::new(),
+        String::from("attach() {"),
+    ];
+
+    if config.attached {
+        lines.push(String::from("  if [ -n \"$TMUX\" ]; then"));
+        lines.push(format!("    {}", switch_client));
+        lines.push(String::from("  else"));
+        lines.push(format!("    {}", attach_session));
+        lines.push(String::from("  fi"));
+    } else {
+        lines.push(String::from("  :"));
+    }
+
+    lines.push(String::from("}"));
+    lines.push(String::new());
+    lines.push(format!("if {} 2>/dev/null; then", has_session));
+    lines.push(String::from("  attach"));
+    lines.push(String::from("  exit 0"));
+    lines.push(String::from("fi"));
+    lines.push(String::new());
+    lines.push(format!("base_index=$({})", base_index));
+    lines.push(format!("pane_base_index=$({})", pane_base_index));
+    lines.push(String::new());
+    lines.extend(session_commands);
+    lines.push(String::new());
+    lines.push(String::from("attach"));
+    lines.push(String::new());
+
+    lines.join("\n")
+}
+
+fn build_tmux_script_target(session_name: &str, target: &str) -> String {
+    // Commands are planned window by window and pane by pane, so whichever
+    // window or pane a command targets is the one which was created last, i.e.
+    // the highest-numbered window and its active pane. Targeting those rather
+    // than indices means the file doesn't depend on the base-index or
+    // pane-base-index of the server which loads it.
+    match target.strip_prefix(&format!("{}:", session_name)) {
+        Some(indices)
+            if indices
+                .split('.')
+                .all(|index| index.parse::<usize>().is_ok()) =>
+        {
+            format!("{}:{{end}}", session_name)
+        }
+        _ => String::from(target),
+    }
+}
+
+fn build_tmux_script_command(session_name: &str, command: TmuxCommand) -> TmuxCommand {
+    let target = |target: String| build_tmux_script_target(session_name, &target);
+    match command {
+        // Without an index, new windows get the next free index.
+        TmuxCommand::NewWindow {
+            window_name,
+            start_directory,
+            ..
+        } => TmuxCommand::NewWindow {
+            target: format!("{}:", session_name),
+            window_name,
+            start_directory,
+        },
+        TmuxCommand::SplitWindow { target: target_ } => TmuxCommand::SplitWindow {
+            target: target(target_),
+        },
+        TmuxCommand::SendKeys {
+            target: target_,
+            command,
+        } => TmuxCommand::SendKeys {
+            target: target(target_),
+            command,
+        },
+        TmuxCommand::SelectLayout {
+            target: target_,
+            layout,
+        } => TmuxCommand::SelectLayout {
+            target: target(target_),
+            layout,
+        },
+        TmuxCommand::SetOption {
+            scope,
+            target: target_,
+            option,
+            value,
+        } => TmuxCommand::SetOption {
+            scope,
+            target: target_.map(target),
+            option,
+            value,
+        },
+        command_ => command_,
+    }
+}
+
+fn build_tmux_script(config: &Config) -> String {
+    let mut lines = vec![format!(
+        "# Generated by rmuxinator from the {} project config.",
+        shell_quote(&config.name)
+    )];
+
+    if let Some(tmux_options) = &config.tmux_options {
+        lines.push(format!(
+            "# NOTE: tmux_options ({}) must be passed to tmux when sourcing this file.",
+            tmux_options
+        ));
+    }
+
+    lines.push(String::new());
+
+    // NOTE: The attach command is rendered as switch-client because the
+    // script is run by a tmux server which can only switch (existing) clients.
+    // The file is loaded by tmux whichever terminal_multiplexer the project
+    // uses and its targets don't depend on the base indices (see
+    // build_tmux_script_target).
+    let tmux_config = Config {
+        terminal_multiplexer: String::from("tmux"),
+        ..config.clone()
+    };
+    let plan_env = PlanEnv {
+        conditions: ConditionEnv::system(),
+        ..PlanEnv::default()
+    };
+    for planned_command in tmux_config.plan(&plan_env) {
+        if let MultiplexerCommand::Tmux(command) = planned_command.command {
+            lines.push(build_tmux_script_command(&config.name, command).to_tmux_script());
+        }
+    }
+
+    lines.push(String::new());
+
+    lines.join("\n")
+}
+
+/// Render a project config as a tmux command file which can be loaded using
+/// `tmux source-file`. Windows and panes are targeted without using their
+/// indices, so the file works regardless of the base-index and
+/// pane-base-index of the server which loads it. As with
+/// export_shell_script, `when` conditions are evaluated on this machine.
+pub fn export_tmux_script(config: &Config) -> String {
+    build_tmux_script(config)
+}
+
+fn kdl_quote(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+enum ZellijNode {
+    Pane(Vec<String>, Vec<String>),
+    Split(&'static str, Vec<ZellijNode>),
+}
+
+fn render_zellij_node(node: &ZellijNode, depth: usize) -> Vec<String> {
+    let indent = "    ".repeat(depth);
+    match node {
+        ZellijNode::Pane(properties, args) => {
+            let mut head = vec![String::from("pane")];
+            head.extend(properties.iter().cloned());
+            if args.is_empty() {
+                vec![format!("{}{}", indent, head.join(" "))]
+            } else {
+                let quoted_args: Vec<String> = args.iter().map(|arg| kdl_quote(arg)).collect();
+                vec![
+                    format!("{}{} {{", indent, head.join(" ")),
+                    format!("{}    args {}", indent, quoted_args.join(" ")),
+                    format!("{}}}", indent),
+                ]
+            }
+        }
+        ZellijNode::Split(split_direction, children) => {
+            let mut lines = vec![format!(
+                "{}pane split_direction={} {{",
+                indent,
+                kdl_quote(split_direction)
+            )];
+            for child in children {
+                lines.extend(render_zellij_node(child, depth + 1));
+            }
+            lines.push(format!("{}}}", indent));
+            lines
+        }
+    }
+}
+
+fn build_zellij_pane(pane: &Pane) -> ZellijNode {
+    let mut properties = vec![];
+    let mut args = vec![];
+
+    if let Some(name) = &pane.name {
+        properties.push(format!("name={}", kdl_quote(name)));
+    }
+
+    if let Some(start_directory) = &pane.start_directory {
+        properties.push(format!("cwd={}", kdl_quote(start_directory)));
+    }
+
+    // Zellij runs a single command in place of the pane's shell, so the
+    // commands are chained and then handed off to an interactive shell in
+    // order to mimic typing them into a tmux pane.
+    if !pane.commands.is_empty() {
+        properties.push(format!("command={}", kdl_quote("sh")));
+        args.push(String::from("-c"));
+        args.push(format!(
+            "{}; exec \"${{SHELL:-sh}}\"",
+            pane.commands.join("; ")
+        ));
+    }
+
+    ZellijNode::Pane(properties, args)
+}
+
+fn build_zellij_tiled_panes(panes: Vec<ZellijNode>) -> (&'static str, Vec<ZellijNode>) {
+    // Approximate tmux's tiled layout using rows of (roughly) equal columns
+    let columns = (panes.len() as f64).sqrt().ceil().max(1.0) as usize;
+    let mut rows = vec![];
+    let mut panes_ = panes.into_iter().peekable();
+    while panes_.peek().is_some() {
+        let mut row: Vec<ZellijNode> = panes_.by_ref().take(columns).collect();
+        if row.len() == 1 {
+            rows.push(row.remove(0));
+        } else {
+            rows.push(ZellijNode::Split("vertical", row));
+        }
+    }
+    if rows.len() == 1 {
+        return match rows.remove(0) {
+            ZellijNode::Split(_, row) => ("vertical", row),
+            pane => ("horizontal", vec![pane]),
+        };
+    }
+    ("horizontal", rows)
+}
+
+fn build_zellij_window_panes(
+    config: &Config,
+    window: &Window,
+    panes: &[(usize, &Pane)],
+    context: &str,
+    warnings: &mut Vec<String>,
+) -> (&'static str, Vec<ZellijNode>) {
+    let mut panes: Vec<ZellijNode> = panes
+        .iter()
+        .map(|(_, pane)| build_zellij_pane(pane))
+        .collect();
+    if panes.is_empty() {
+        panes.push(ZellijNode::Pane(vec![], vec![]));
+    }
+
+    if !window.responsive_layouts.is_empty() {
+        warnings.push(format!(
+            "{}.responsive_layouts: unsupported; the window's layout is used instead",
+            context
+        ));
+    }
+
+    // See select_window_layout re: precedence
+    let layout = window
+        .layout
+        .as_ref()
+        .or(config.layout.as_ref())
+        .map(|layout| layout.to_string());
+
+    if panes.len() == 1 {
+        return ("horizontal", panes);
+    }
+
+    match layout.as_deref() {
+        Some("even-horizontal") => ("vertical", panes),
+        Some("even-vertical") => ("horizontal", panes),
+        Some("main-horizontal") => {
+            let main_pane = panes.remove(0);
+            (
+                "horizontal",
+                vec![main_pane, ZellijNode::Split("vertical", panes)],
+            )
+        }
+        Some("main-vertical") => {
+            let main_pane = panes.remove(0);
+            (
+                "vertical",
+                vec![main_pane, ZellijNode::Split("horizontal", panes)],
+            )
+        }
+        Some("tiled") | None => build_zellij_tiled_panes(panes),
+        Some(custom_layout) => {
+            warnings.push(format!(
+                "{}: custom layout ({}) can't be converted; panes are tiled instead",
+                context, custom_layout
+            ));
+            build_zellij_tiled_panes(panes)
+        }
+    }
+}
+
+/// Render a project config as a Zellij KDL layout. Any warnings about
+/// settings which could not be (faithfully) converted are returned alongside
+/// it. As when starting the session, windows and panes whose `when`
+/// conditions don't hold on this machine are left out.
+pub fn export_zellij_layout(config: &Config) -> (String, Vec<String>) {
+    // The same windows and panes as would be planned (see Config::plan)
+    let config = &*expand_for_planning(config);
+    let mut warnings = vec![];
+
+    if !config.hooks.is_empty() {
+        warnings.push(String::from("hooks: unsupported by Zellij"));
+    }
+    if config.tmux_options.is_some() {
+        warnings.push(String::from("tmux_options: unsupported by Zellij"));
+    }
+    if !config.attached {
+        warnings.push(String::from(
+            "attached: Zellij always attaches to sessions started from a layout",
+        ));
+    }
+
+    let mut lines = vec![String::from("layout {")];
+
+    if let Some(start_directory) = &config.start_directory {
+        lines.push(format!("    cwd {}", kdl_quote(start_directory)));
+    }
+
+    for selected_window in select_windows(config, &ConditionEnv::system()) {
+        let window = selected_window.window;
+        let context = format!("windows[{}]", selected_window.position);
+        let mut properties = vec![];
+        if let Some(name) = &window.name {
+            properties.push(format!("name={}", kdl_quote(name)));
+        }
+        if let Some(start_directory) = &window.start_directory {
+            properties.push(format!("cwd={}", kdl_quote(start_directory)));
+        }
+
+        let (split_direction, panes) = build_zellij_window_panes(
+            config,
+            window,
+            &selected_window.panes,
+            &context,
+            &mut warnings,
+        );
+        properties.push(format!("split_direction={}", kdl_quote(split_direction)));
+
+        lines.push(format!("    tab {} {{", properties.join(" ")));
+        for pane in panes.iter() {
+            lines.extend(render_zellij_node(pane, 2));
+        }
+        lines.push(String::from("    }"));
+    }
+
+    lines.push(String::from("}"));
+    lines.push(String::new());
+
+    let has_variables = config
+        .windows
+        .iter()
+        .flat_map(|window| {
+            window
+                .panes
+                .iter()
+                .map(|pane| &pane.start_directory)
+                .chain(std::iter::once(&window.start_directory))
+        })
+        .chain(std::iter::once(&config.start_directory))
+        .any(|start_directory| {
+            start_directory
+                .as_ref()
+                .is_some_and(|start_directory_| start_directory_.contains('$'))
+        });
+    if has_variables {
+        warnings.push(String::from(
+            "start_directory: environment variables are not expanded by Zellij",
+        ));
+    }
+
+    (lines.join("\n"), warnings)
+}
+
+fn has_conditions(config: &Config) -> bool {
+    config
+        .windows
+        .iter()
+        .any(|window| window.when.is_some() || window.panes.iter().any(|pane| pane.when.is_some()))
+}
+
+pub fn run_export(
+    config: Config,
+    format: &str,
+    output_path: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    let (contents, warnings) = match format {
+        "sh" => (export_shell_script(&config), vec![]),
+        "tmux" => (export_tmux_script(&config), vec![]),
+        "zellij" => export_zellij_layout(&config),
+        "json" | "toml" | "yaml" => (config.to_format(format.parse::<ConfigFormat>()?)?, vec![]),
+        _ => return Err(format!("Unsupported export format: {}", format).into()),
+    };
+
+    if has_conditions(&config) && matches!(format, "sh" | "tmux" | "zellij") {
+        eprintln!(
+            "Warning: when: conditions were evaluated on this machine; windows and panes whose conditions don't hold here have been left out"
+        );
+    }
+    for warning in warnings {
+        eprintln!("Warning: {}", warning);
+    }
+
+    write_output(&contents, output_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ForEach, Hook, HookName, Layout};
+
+    #[test]
+    fn it_doesnt_quote_safe_shell_args() {
+        let expected = "/tmp/foo.conf";
+        let actual = shell_quote("/tmp/foo.conf");
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn it_quotes_unsafe_shell_args() {
+        assert_eq!("''", shell_quote(""));
+        assert_eq!("'echo $HOME'", shell_quote("echo $HOME"));
+        assert_eq!("'it'\\''s'", shell_quote("it's"));
+        assert_eq!("';'", shell_quote(";"));
+        assert_eq!("'~/$HOME #{pane_id}'", shell_quote("~/$HOME #{pane_id}"));
+    }
+
+    #[test]
+    fn it_offsets_targets_by_the_runtime_base_indices() {
+        assert_eq!(
+            Some(String::from("'a session':\"$((base_index + 1))\"")),
+            build_shell_target("a session", "a session:1")
+        );
+        assert_eq!(
+            Some(String::from(
+                "foo:\"$((base_index + 1)).$((pane_base_index + 2))\""
+            )),
+            build_shell_target("foo", "foo:1.2")
+        );
+        assert_eq!(None, build_shell_target("foo", "bar:1"));
+    }
+
+    #[test]
+    fn it_exports_a_shell_script() {
+        let config = Config {
+            attached: true,
+            name: String::from("foo"),
+            terminal_multiplexer: String::from("tmux"),
+            tmux_options: Some(String::from("-L work")),
+            windows: vec![Window {
+                name: Some(String::from("one")),
+                panes: vec![Pane {
+                    commands: vec![String::from("echo 'hi there'")],
+                    ..Pane::default()
+                }],
+                ..Window::default()
+            }],
+            ..Config::default()
+        };
+        let expected = r#"#!/bin/sh
+# Generated by rmuxinator from the foo project config.
+
+attach() {
+  if [ -n "$TMUX" ]; then
+    tmux -L work -u switch-client -t foo
+  else
+    tmux -L work -u attach-session -t foo
+  fi
+}
+
+if tmux -L work has-session -t =foo 2>/dev/null; then
+  attach
+  exit 0
+fi
+
+base_index=$(tmux -L work start-server ';' show-option -gv base-index)
+pane_base_index=$(tmux -L work start-server ';' show-window-option -gv pane-base-index)
+
+tmux -L work new-session -d -s foo -n one
+tmux -L work send-keys -t foo:"$((base_index + 0)).$((pane_base_index + 0))" 'echo '\''hi there'\''' Enter
+
+attach
+"#;
+        let actual = export_shell_script(&config);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn it_exports_a_tmux_script() {
+        let config = Config {
+            attached: true,
+            hooks: vec![Hook {
+                command: String::from("run-shell \"echo 'hi'\""),
+                name: HookName::PaneFocusIn,
+            }],
+            name: String::from("foo"),
+            terminal_multiplexer: String::from("tmux"),
+            tmux_options: Some(String::from("-L work")),
+            windows: vec![
+                Window {
+                    name: Some(String::from("one")),
+                    panes: vec![Pane {
+                        commands: vec![String::from("echo $HOME")],
+                        ..Pane::default()
+                    }],
+                    ..Window::default()
+                },
+                Window {
+                    layout: Some(Layout::Tiled),
+                    panes: vec![
+                        Pane::default(),
+                        Pane {
+                            commands: vec![String::from("top")],
+                            ..Pane::default()
+                        },
+                    ],
+                    ..Window::default()
+                },
+            ],
+            ..Config::default()
+        };
+        let expected = r#"# Generated by rmuxinator from the foo project config.
+# NOTE: tmux_options (-L work) must be passed to tmux when sourcing this file.
+
+new-session -d -s foo -n one
+set-hook -a pane-focus-in 'run-shell "echo '\''hi'\''"'
+send-keys -t 'foo:{end}' 'echo $HOME' Enter
+new-window -t foo:
+split-window -t 'foo:{end}'
+select-layout -t 'foo:{end}' tiled
+send-keys -t 'foo:{end}' top Enter
+select-layout -t 'foo:{end}' tiled
+switch-client -t foo
+"#;
+        let actual = export_tmux_script(&config);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn it_expands_for_each_in_every_export_format() {
+        let config = Config {
+            name: String::from("foo"),
+            windows: vec![Window {
+                name: Some(String::from("{{ item }}")),
+                for_each: Some(ForEach::Items(vec![
+                    String::from("api"),
+                    String::from("web"),
+                ])),
+                ..Window::default()
+            }],
+            terminal_multiplexer: String::from("tmux"),
+            ..Config::default()
+        };
+
+        let shell_script = export_shell_script(&config);
+        assert!(shell_script.contains("tmux new-session -d -s foo -n api"));
+        assert!(shell_script.contains("-n web"));
+
+        let tmux_script = export_tmux_script(&config);
+        assert!(tmux_script.contains("new-session -d -s foo -n api\nnew-window -t foo: -n web\n"));
+
+        let (zellij_layout, _) = export_zellij_layout(&config);
+        assert!(zellij_layout.contains("tab name=\"api\""));
+        assert!(zellij_layout.contains("tab name=\"web\""));
+        assert!(!zellij_layout.contains("{{ item }}"));
+    }
+
+    #[test]
+    fn it_exports_a_zellij_layout() {
+        let config = Config {
+            attached: true,
+            layout: Some(Layout::Custom(String::from("main-vertical"))),
+            name: String::from("foo"),
+            start_directory: Some(String::from("/tmp")),
+            windows: vec![Window {
+                name: Some(String::from("one")),
+                panes: vec![
+                    Pane {
+                        commands: vec![String::from("echo \"hi\""), String::from("ls")],
+                        name: Some(String::from("main")),
+                        ..Pane::default()
+                    },
+                    Pane {
+                        start_directory: Some(String::from("/var/log")),
+                        ..Pane::default()
+                    },
+                    Pane::default(),
+                ],
+                ..Window::default()
+            }],
+            ..Config::default()
+        };
+        let expected = r#"layout {
+    cwd "/tmp"
+    tab name="one" split_direction="vertical" {
+        pane name="main" command="sh" {
+            args "-c" "echo \"hi\"; ls; exec \"${SHELL:-sh}\""
+        }
+        pane split_direction="horizontal" {
+            pane cwd="/var/log"
+            pane
+        }
+    }
+}
+"#;
+        let (actual, warnings) = export_zellij_layout(&config);
+        assert_eq!(expected, actual);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn it_tiles_zellij_panes_in_rows() {
+        let panes = (0..5).map(|_| ZellijNode::Pane(vec![], vec![])).collect();
+        let (split_direction, rows) = build_zellij_tiled_panes(panes);
+        let row_lengths: Vec<usize> = rows
+            .iter()
+            .map(|row| match row {
+                ZellijNode::Split(_, columns) => columns.len(),
+                ZellijNode::Pane(_, _) => 1,
+            })
+            .collect();
+        assert_eq!("horizontal", split_direction);
+        assert_eq!(vec![3, 2], row_lengths);
+    }
+
+    #[test]
+    fn it_warns_about_settings_which_dont_map_to_zellij() {
+        let config = Config {
+            attached: true,
+            hooks: vec![Hook {
+                command: String::from("run-shell \"echo 'hi'\""),
+                name: HookName::PaneFocusIn,
+            }],
+            name: String::from("foo"),
+            windows: vec![Window {
+                layout: Some(Layout::Custom(String::from("b25f,80x24,0,0,2"))),
+                panes: vec![Pane::default(), Pane::default()],
+                start_directory: Some(String::from("$HOME/src")),
+                ..Window::default()
+            }],
+            ..Config::default()
+        };
+        let expected = vec![
+            String::from("hooks: unsupported by Zellij"),
+            String::from(
+                "windows[0]: custom layout (b25f,80x24,0,0,2) can't be converted; panes are tiled instead",
+            ),
+            String::from("start_directory: environment variables are not expanded by Zellij"),
+        ];
+        let (_, warnings) = export_zellij_layout(&config);
+        assert_eq!(expected, warnings);
+    }
+}
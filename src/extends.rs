@@ -0,0 +1,334 @@
+use crate::format::ConfigFormat;
+use crate::include::resolve_includes;
+use crate::parse_error::{format_json_error, format_toml_error, format_yaml_error};
+use crate::{expand_home, EnvProvider};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Support for `extends = "base.toml"`, which merges a project config into
+// another one. Merging is done before the config is deserialized so that
+// base configs can be partial (e.g. without a `name`) and so that values
+// which are set in the extending config can be told apart from defaults.
+
+const EXTENDS_KEY: &str = "extends";
+const EXTENDED_CONFIG: &str = "extended config file";
+
+/// Parse a project config without deserializing it into a Config.
+pub fn parse_value(
+    contents: &str,
+    format: ConfigFormat,
+    source_name: &str,
+) -> Result<Value, String> {
+    match format {
+        ConfigFormat::Json => serde_json::from_str(contents)
+            .map_err(|error| format_json_error(source_name, contents, &error)),
+        ConfigFormat::Toml => toml::from_str(contents)
+            .map_err(|error| format_toml_error(source_name, contents, &error)),
+        ConfigFormat::Yaml => serde_yaml::from_str(contents)
+            .map_err(|error| format_yaml_error(source_name, contents, &error)),
+    }
+}
+
+pub fn has_extends(value: &Value) -> bool {
+    value.get(EXTENDS_KEY).is_some()
+}
+
+/// The directory which shared project configs can be kept in, i.e.
+/// $XDG_CONFIG_HOME/rmuxinator or ~/.config/rmuxinator.
+fn config_dir(env: &dyn EnvProvider) -> Option<PathBuf> {
+    env.get_var("XDG_CONFIG_HOME")
+        .filter(|xdg_config_home| !xdg_config_home.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env.get_var("HOME")
+                .map(|home| Path::new(&home).join(".config"))
+        })
+        .map(|config_home| config_home.join("rmuxinator"))
+}
+
+/// Find a config file named by e.g. `extends` or `include`, which is relative
+/// to the file which names it or, failing that, the config dir.
+pub fn resolve_config_path(
+    path: &str,
+    description: &str,
+    source_name: &str,
+    env: &dyn EnvProvider,
+) -> Result<PathBuf, String> {
+    let expanded = expand_home(path, env);
+    if expanded.is_absolute() {
+        return Ok(expanded);
+    }
+
+    let source_dir = Path::new(source_name)
+        .parent()
+        .unwrap_or_else(|| Path::new(""));
+    let candidates: Vec<PathBuf> = std::iter::once(source_dir.join(&expanded))
+        .chain(config_dir(env).map(|config_dir_| config_dir_.join(&expanded)))
+        .collect();
+
+    candidates
+        .iter()
+        .find(|candidate| candidate.is_file())
+        .cloned()
+        .ok_or_else(|| {
+            format!(
+                "Unable to find {} \"{}\" (looked for {})",
+                description,
+                path,
+                candidates
+                    .iter()
+                    .map(|candidate| candidate.display().to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )
+        })
+}
+
+/// The canonical path of a config file (used to detect cycles).
+pub fn canonicalize_config_path(path: &Path, description: &str) -> Result<PathBuf, String> {
+    path.canonicalize()
+        .map_err(|_| format!("Unable to open {} {}", description, path.display()))
+}
+
+/// Read and parse a config file named by another config file. Its format is
+/// inferred from its extension, falling back to `format` (i.e. that of the
+/// file which names it).
+pub fn load_config_value(
+    path: &Path,
+    description: &str,
+    format: ConfigFormat,
+) -> Result<(Value, ConfigFormat), String> {
+    let name = path.display().to_string();
+    let contents =
+        fs::read_to_string(path).map_err(|_| format!("Unable to read {} {}", description, name))?;
+    let format_ = ConfigFormat::from_path(&name).unwrap_or(format);
+    parse_value(&contents, format_, &name).map(|value| (value, format_))
+}
+
+/// Describe a cycle of config files, e.g. `a.toml -> b.toml -> a.toml`.
+pub fn describe_cycle(chain: &[PathBuf], path: &Path) -> String {
+    chain
+        .iter()
+        .map(|path_| path_.as_path())
+        .chain(std::iter::once(path))
+        .map(|path_| path_.display().to_string())
+        .collect::<Vec<String>>()
+        .join(" -> ")
+}
+
+/// Windows are replaced by windows with the same name and appended
+/// otherwise.
+fn merge_windows(mut windows: Vec<Value>, extending_windows: Vec<Value>) -> Vec<Value> {
+    for window in extending_windows {
+        let existing_window = window.get("name").and_then(Value::as_str).and_then(|name| {
+            windows
+                .iter_mut()
+                .find(|existing| existing.get("name").and_then(Value::as_str) == Some(name))
+        });
+        match existing_window {
+            Some(existing) => *existing = window,
+            None => windows.push(window),
+        }
+    }
+    windows
+}
+
+/// Merge a project config (`extending`) into the config it extends (`base`).
+/// Hooks are appended, windows are appended or replace base windows with the
+/// same name, `layouts`, `profiles` and `variables` entries are merged and
+/// everything else overrides the base config.
+pub fn merge_values(base: Value, extending: Value) -> Value {
+    match (base, extending) {
+        (Value::Object(mut base_), Value::Object(extending_)) => {
+            for (key, value) in extending_ {
+                let merged = match (key.as_str(), base_.remove(&key), value) {
+                    ("hooks", Some(Value::Array(mut hooks)), Value::Array(extending_hooks)) => {
+                        hooks.extend(extending_hooks);
+                        Value::Array(hooks)
+                    }
+                    ("windows", Some(Value::Array(windows)), Value::Array(extending_windows)) => {
+                        Value::Array(merge_windows(windows, extending_windows))
+                    }
+                    (
+                        "layouts" | "profiles" | "variables",
+                        Some(Value::Object(mut table)),
+                        Value::Object(extending_table),
+                    ) => {
+                        table.extend(extending_table);
+                        Value::Object(table)
+                    }
+                    (_, _, value_) => value_,
+                };
+                base_.insert(key, merged);
+            }
+            Value::Object(base_)
+        }
+        (_, extending_) => extending_,
+    }
+}
+
+/// Merge the config named by `value`'s `extends` (and any configs which it
+/// extends in turn) into `value`.
+pub fn resolve_extends(
+    value: Value,
+    format: ConfigFormat,
+    source_name: &str,
+    env: &dyn EnvProvider,
+) -> Result<Value, String> {
+    let mut chain: Vec<PathBuf> = Path::new(source_name).canonicalize().into_iter().collect();
+    resolve_extends_chain(value, format, source_name, env, &mut chain)
+}
+
+fn resolve_extends_chain(
+    value: Value,
+    format: ConfigFormat,
+    source_name: &str,
+    env: &dyn EnvProvider,
+    chain: &mut Vec<PathBuf>,
+) -> Result<Value, String> {
+    let mut object: Map<String, Value> = match value {
+        Value::Object(object_) => object_,
+        _ => return Ok(value),
+    };
+    let extends = match object.remove(EXTENDS_KEY) {
+        Some(Value::String(extends_)) => extends_,
+        Some(_) => return Err(format!("{}: extends must be a string", source_name)),
+        None => return Ok(Value::Object(object)),
+    };
+
+    let base_path = resolve_config_path(&extends, EXTENDED_CONFIG, source_name, env)
+        .map_err(|error| format!("{}: {}", source_name, error))?;
+    let canonical_base_path = canonicalize_config_path(&base_path, EXTENDED_CONFIG)
+        .map_err(|error| format!("{}: {}", source_name, error))?;
+    if chain.contains(&canonical_base_path) {
+        return Err(format!(
+            "{}: extends cycle detected ({})",
+            source_name,
+            describe_cycle(chain, &canonical_base_path)
+        ));
+    }
+
+    let base_name = base_path.display().to_string();
+    let (base_value, base_format) = load_config_value(&base_path, EXTENDED_CONFIG, format)
+        .map_err(|error| format!("{}: {}", source_name, error))?;
+    let base_value = resolve_includes(base_value, base_format, &base_name, env)?;
+
+    chain.push(canonical_base_path);
+    let base_value = resolve_extends_chain(base_value, base_format, &base_name, env, chain)?;
+    chain.pop();
+
+    Ok(merge_values(base_value, Value::Object(object)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{write_file, MockEnv};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    #[test]
+    fn it_merges_values() {
+        let base = serde_json::json!({
+            "attached": false,
+            "hooks": [{"name": "client-attached", "command": "run-shell one"}],
+            "layouts": {"wide": "main-vertical", "tall": "main-horizontal"},
+            "tmux_options": "-L base",
+            "windows": [
+                {"name": "editor", "panes": [{"commands": ["vim"]}]},
+                {"name": "logs", "panes": [{"commands": ["tail -f log"]}]}
+            ]
+        });
+        let extending = serde_json::json!({
+            "name": "foo",
+            "hooks": [{"name": "client-attached", "command": "run-shell two"}],
+            "layouts": {"wide": "even-horizontal"},
+            "tmux_options": "-L foo",
+            "windows": [
+                {"name": "logs", "panes": [{"commands": ["journalctl -f"]}]},
+                {"panes": [{"commands": ["htop"]}]}
+            ]
+        });
+        let expected = serde_json::json!({
+            "attached": false,
+            "hooks": [
+                {"name": "client-attached", "command": "run-shell one"},
+                {"name": "client-attached", "command": "run-shell two"}
+            ],
+            "layouts": {"wide": "even-horizontal", "tall": "main-horizontal"},
+            "name": "foo",
+            "tmux_options": "-L foo",
+            "windows": [
+                {"name": "editor", "panes": [{"commands": ["vim"]}]},
+                {"name": "logs", "panes": [{"commands": ["journalctl -f"]}]},
+                {"panes": [{"commands": ["htop"]}]}
+            ]
+        });
+        assert_eq!(expected, merge_values(base, extending));
+    }
+
+    #[test]
+    fn it_resolves_extends_relative_to_the_file_then_the_config_dir() {
+        let project_dir = TempDir::new().unwrap();
+        let config_home = TempDir::new().unwrap();
+        write_file(
+            project_dir.path(),
+            "shared/base.yml",
+            "extends: common.toml\ntmux_options: -L base\n",
+        );
+        write_file(
+            config_home.path(),
+            "rmuxinator/common.toml",
+            "attached = false\ntmux_options = \"-L common\"\n",
+        );
+        let project_path = write_file(project_dir.path(), "project.toml", "");
+        let env = MockEnv(HashMap::from([(
+            String::from("XDG_CONFIG_HOME"),
+            config_home.path().display().to_string(),
+        )]));
+
+        let value = serde_json::json!({"extends": "shared/base.yml", "name": "foo"});
+        let actual = resolve_extends(value, ConfigFormat::Toml, &project_path, &env).unwrap();
+        let expected = serde_json::json!({
+            "attached": false,
+            "name": "foo",
+            "tmux_options": "-L base"
+        });
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn it_detects_extends_cycles() {
+        let project_dir = TempDir::new().unwrap();
+        let one = write_file(project_dir.path(), "one.toml", "extends = \"two.toml\"\n");
+        write_file(project_dir.path(), "two.toml", "extends = \"one.toml\"\n");
+        let env = MockEnv(HashMap::new());
+
+        let value = serde_json::json!({"extends": "two.toml"});
+        let actual = resolve_extends(value, ConfigFormat::Toml, &one, &env).unwrap_err();
+        assert!(
+            actual.contains("two.toml: extends cycle detected"),
+            "{}",
+            actual
+        );
+    }
+
+    #[test]
+    fn it_reports_missing_extended_configs() {
+        let project_dir = TempDir::new().unwrap();
+        let project_path = write_file(project_dir.path(), "project.toml", "");
+        let env = MockEnv(HashMap::new());
+
+        let value = serde_json::json!({"extends": "missing.toml"});
+        let actual = resolve_extends(value, ConfigFormat::Toml, &project_path, &env).unwrap_err();
+        assert_eq!(
+            format!(
+                "{}: Unable to find extended config file \"missing.toml\" (looked for {})",
+                project_path,
+                project_dir.path().join("missing.toml").display()
+            ),
+            actual
+        );
+    }
+}
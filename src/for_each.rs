@@ -0,0 +1,365 @@
+use crate::condition::Condition;
+use crate::glob::matches_glob;
+use crate::template::render_known_templates;
+use crate::{expand_home, Config, EnvProvider, Pane, SystemEnv, Window};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// What to repeat a window or pane over.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ForEach {
+    /// e.g. `for_each = ["api", "worker", "web"]`
+    Items(Vec<String>),
+    /// A glob over directories, e.g. `for_each = "~/src/services/*"`
+    Glob(String),
+}
+
+fn is_glob_pattern(component: &str) -> bool {
+    component.contains(['*', '?'])
+}
+
+fn matches_glob_component(pattern: &str, name: &str) -> bool {
+    // Like shells, wildcards don't match hidden entries.
+    if name.starts_with('.') && !pattern.starts_with('.') {
+        return false;
+    }
+    matches_glob(pattern, name)
+}
+
+/// The entries of `dir` (or of the current directory, if it's empty) which
+/// match the glob `component`, sorted by name.
+fn glob_entries(dir: &Path, component: &str) -> Vec<PathBuf> {
+    let dir_ = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    let mut matches: Vec<PathBuf> = fs::read_dir(dir_)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|entry| {
+                    entry
+                        .file_name()
+                        .to_str()
+                        .is_some_and(|name| matches_glob_component(component, name))
+                })
+                .map(|entry| dir.join(entry.file_name()))
+                .collect()
+        })
+        .unwrap_or_default();
+    matches.sort();
+    matches
+}
+
+/// The directories which match `pattern` (in which `*` and `?` match any
+/// characters and any one character within a path component). Relative
+/// patterns are resolved against `base_dir`.
+fn glob_directories(pattern: &str, base_dir: &Path, env: &dyn EnvProvider) -> Vec<String> {
+    let expanded = expand_home(pattern, env);
+    let mut paths = vec![if expanded.is_absolute() {
+        PathBuf::new()
+    } else {
+        base_dir.to_path_buf()
+    }];
+
+    for component in expanded.components() {
+        paths = match component.as_os_str().to_str() {
+            Some(glob) if is_glob_pattern(glob) => paths
+                .iter()
+                .flat_map(|path| glob_entries(path, glob))
+                .collect(),
+            _ => paths.iter().map(|path| path.join(component)).collect(),
+        };
+    }
+
+    paths
+        .into_iter()
+        .filter(|path| path.is_dir())
+        .map(|path| path.display().to_string())
+        .collect()
+}
+
+impl ForEach {
+    fn items(&self, base_dir: &Path, env: &dyn EnvProvider) -> Vec<String> {
+        match self {
+            ForEach::Items(items) => items.clone(),
+            ForEach::Glob(pattern) => glob_directories(pattern, base_dir, env),
+        }
+    }
+}
+
+/// Fill in `{{ item }}`, `{{ item_name }}` (the last component of the item,
+/// e.g. a directory's name) and `{{ index }}`, leaving any other templates for
+/// `Config::apply_variables`.
+fn render_item(value: &mut String, item: &str, index: usize) {
+    let item_name = Path::new(item)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(item);
+    let lookup = |name: &str| match name {
+        "item" => Some(String::from(item)),
+        "item_name" => Some(String::from(item_name)),
+        "index" => Some(index.to_string()),
+        _ => None,
+    };
+    *value = render_known_templates(value, &lookup);
+}
+
+fn render_optional_item(value: &mut Option<String>, item: &str, index: usize) {
+    if let Some(value_) = value.as_mut() {
+        render_item(value_, item, index);
+    }
+}
+
+fn render_condition(condition: &mut Option<Condition>, item: &str, index: usize) {
+    if let Some(condition_) = condition.as_mut() {
+        render_optional_item(&mut condition_.command, item, index);
+        render_optional_item(&mut condition_.env, item, index);
+        render_optional_item(&mut condition_.file, item, index);
+        render_optional_item(&mut condition_.hostname, item, index);
+        render_optional_item(&mut condition_.os, item, index);
+    }
+}
+
+fn render_pane(pane: &mut Pane, item: &str, index: usize) {
+    for command in pane.commands.iter_mut() {
+        render_item(command, item, index);
+    }
+    render_optional_item(&mut pane.name, item, index);
+    render_optional_item(&mut pane.start_directory, item, index);
+    render_condition(&mut pane.when, item, index);
+}
+
+fn render_window(window: &mut Window, item: &str, index: usize) {
+    render_optional_item(&mut window.name, item, index);
+    render_optional_item(&mut window.start_directory, item, index);
+    render_condition(&mut window.when, item, index);
+    for pane in window.panes.iter_mut() {
+        render_pane(pane, item, index);
+    }
+}
+
+/// Replace each value which has a `for_each` with a copy per item.
+fn expand<T: Clone>(
+    values: Vec<T>,
+    for_each: fn(&mut T) -> &mut Option<ForEach>,
+    render: fn(&mut T, &str, usize),
+    base_dir: &Path,
+    env: &dyn EnvProvider,
+) -> Vec<T> {
+    let mut expanded = vec![];
+    for mut value in values {
+        match for_each(&mut value).take() {
+            Some(for_each_) => {
+                for (index, item) in for_each_.items(base_dir, env).iter().enumerate() {
+                    let mut copy = value.clone();
+                    render(&mut copy, item, index);
+                    expanded.push(copy);
+                }
+            }
+            None => expanded.push(value),
+        }
+    }
+    expanded
+}
+
+impl Config {
+    /// Replace each window and pane which has a `for_each` with a copy per
+    /// item, filling in `{{ item }}`, `{{ item_name }}` and `{{ index }}` in
+    /// its names, commands, start directories and conditions. Panes are
+    /// expanded first, so a pane's items take precedence over its window's.
+    ///
+    /// Relative globs are resolved against the directory of the config file
+    /// which the config was loaded from or, for configs which weren't (e.g.
+    /// which were read from stdin), the current directory.
+    ///
+    /// NOTE: Configs are expanded when they're loaded (and when a profile is
+    /// applied), i.e. before `apply_variables` (which would report `item` and
+    /// `index` as unresolved). Configs which are built in code are expanded
+    /// when they're planned, if they haven't been already.
+    pub fn expand_for_each(&mut self) {
+        self.expand_for_each_with_env(&SystemEnv)
+    }
+
+    fn expand_for_each_with_env(&mut self, env: &dyn EnvProvider) {
+        let base_dir = self.source_dir.clone().unwrap_or_default();
+        for window in self.windows.iter_mut() {
+            let panes = std::mem::take(&mut window.panes);
+            window.panes = expand(
+                panes,
+                |pane| &mut pane.for_each,
+                render_pane,
+                &base_dir,
+                env,
+            );
+        }
+        let windows = std::mem::take(&mut self.windows);
+        self.windows = expand(
+            windows,
+            |window| &mut window.for_each,
+            render_window,
+            &base_dir,
+            env,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{write_file, MockEnv};
+    use std::collections::{BTreeMap, HashMap};
+    use tempfile::TempDir;
+
+    #[test]
+    fn it_expands_windows_and_panes() {
+        let mut config = Config {
+            windows: vec![
+                Window {
+                    name: Some(String::from("{{ item }}")),
+                    for_each: Some(ForEach::Items(vec![
+                        String::from("api"),
+                        String::from("web"),
+                    ])),
+                    panes: vec![
+                        Pane {
+                            commands: vec![String::from("ssh {{ item }}-{{ index }}")],
+                            for_each: Some(ForEach::Items(vec![
+                                String::from("host-a"),
+                                String::from("host-b"),
+                            ])),
+                            ..Pane::default()
+                        },
+                        Pane {
+                            commands: vec![String::from(
+                                "make run SERVICE={{ item }} PORT={{ port | default(8080) }}",
+                            )],
+                            start_directory: Some(String::from("~/src/{{ item }}")),
+                            ..Pane::default()
+                        },
+                    ],
+                    ..Window::default()
+                },
+                Window {
+                    name: Some(String::from("{{ role }}")),
+                    ..Window::default()
+                },
+            ],
+            ..Config::default()
+        };
+        config.expand_for_each_with_env(&MockEnv(HashMap::new()));
+
+        let window_names: Vec<Option<String>> = config
+            .windows
+            .iter()
+            .map(|window| window.name.clone())
+            .collect();
+        assert_eq!(
+            vec![
+                Some(String::from("api")),
+                Some(String::from("web")),
+                Some(String::from("{{ role }}"))
+            ],
+            window_names
+        );
+        let web_panes = &config.windows[1].panes;
+        assert_eq!(
+            vec![
+                vec!["ssh host-a-0"],
+                vec!["ssh host-b-1"],
+                vec!["make run SERVICE=web PORT={{ port | default(8080) }}"]
+            ],
+            web_panes
+                .iter()
+                .map(|pane| pane.commands.clone())
+                .collect::<Vec<Vec<String>>>()
+        );
+        assert_eq!(
+            Some(String::from("~/src/web")),
+            web_panes[2].start_directory
+        );
+        assert_eq!(None, config.windows[0].for_each);
+
+        // Other templates (and their defaults) are left for apply_variables.
+        config
+            .apply_variables(&BTreeMap::from([
+                (String::from("port"), String::from("9000")),
+                (String::from("role"), String::from("db")),
+            ]))
+            .unwrap();
+        assert_eq!(
+            vec!["make run SERVICE=web PORT=9000"],
+            config.windows[1].panes[2].commands
+        );
+    }
+
+    #[test]
+    fn it_expands_globs_over_directories() {
+        let home = TempDir::new().unwrap();
+        for dir in ["src/worker", "src/api", "src/.cache", "docs/api"] {
+            fs::create_dir_all(home.path().join(dir)).unwrap();
+        }
+        fs::write(home.path().join("src/README.md"), "").unwrap();
+        let env = MockEnv(HashMap::from([(
+            String::from("HOME"),
+            home.path().display().to_string(),
+        )]));
+
+        let actual = ForEach::Glob(String::from("~/src/*")).items(Path::new(""), &env);
+        let expected: Vec<String> = ["src/api", "src/worker"]
+            .iter()
+            .map(|dir| home.path().join(dir).display().to_string())
+            .collect();
+        assert_eq!(expected, actual);
+
+        let actual = ForEach::Glob(String::from("~/*/ap?")).items(Path::new(""), &env);
+        let expected: Vec<String> = ["docs/api", "src/api"]
+            .iter()
+            .map(|dir| home.path().join(dir).display().to_string())
+            .collect();
+        assert_eq!(expected, actual);
+
+        let mut window = Window {
+            name: Some(String::from("{{ item_name }}")),
+            start_directory: Some(String::from("{{ item }}")),
+            ..Window::default()
+        };
+        render_window(&mut window, &expected[1], 1);
+        assert_eq!(Some(String::from("api")), window.name);
+        assert_eq!(Some(expected[1].clone()), window.start_directory);
+    }
+
+    #[test]
+    fn it_resolves_relative_globs_against_the_config_files_directory() {
+        let project_dir = TempDir::new().unwrap();
+        for dir in ["services/api", "services/worker"] {
+            fs::create_dir_all(project_dir.path().join(dir)).unwrap();
+        }
+        let config_path = write_file(
+            project_dir.path(),
+            "Foo.toml",
+            r#"
+            name = "foo"
+            [[windows]]
+              name = "{{ item_name }}"
+              for_each = "services/*"
+              start_directory = "{{ item }}"
+            "#,
+        );
+
+        let config = Config::new_from_file_path(&config_path).unwrap();
+        let actual: Vec<Option<String>> = config
+            .windows
+            .iter()
+            .map(|window| window.start_directory.clone())
+            .collect();
+        let expected: Vec<Option<String>> = ["services/api", "services/worker"]
+            .iter()
+            .map(|dir| Some(project_dir.path().join(dir).display().to_string()))
+            .collect();
+        assert_eq!(expected, actual);
+    }
+}
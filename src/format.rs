@@ -0,0 +1,282 @@
+use crate::extends::{has_extends, parse_value, resolve_extends};
+use crate::include::{has_includes, resolve_includes};
+use crate::parse_error::{format_json_error, format_toml_error, format_yaml_error};
+use crate::{Config, SystemEnv};
+use std::fmt;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A file format which project configs can be read from and written to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    pub const NAMES: [&'static str; 3] = ["json", "toml", "yaml"];
+
+    /// Infer the format from a file's extension (i.e. .json, .toml, .yaml or
+    /// .yml).
+    pub fn from_path(path: &str) -> Option<ConfigFormat> {
+        Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(|extension| ConfigFormat::from_str(&extension.to_lowercase()).ok())
+    }
+}
+
+impl FromStr for ConfigFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(ConfigFormat::Json),
+            "toml" => Ok(ConfigFormat::Toml),
+            "yaml" | "yml" => Ok(ConfigFormat::Yaml),
+            _ => Err(format!("Unsupported config format: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigFormat::Json => write!(f, "json"),
+            ConfigFormat::Toml => write!(f, "toml"),
+            ConfigFormat::Yaml => write!(f, "yaml"),
+        }
+    }
+}
+
+impl Config {
+    /// Parse a project config, resolve the windows and panes it `include`s,
+    /// merge it into any config it `extends` and resolve references to named
+    /// layouts. `source_name` is the file's path
+    /// (used in error messages, to find extended configs and to resolve
+    /// relative `for_each` globs).
+    pub fn from_str_with_format(
+        contents: &str,
+        format: ConfigFormat,
+        source_name: &str,
+    ) -> Result<Config, String> {
+        let value = parse_value(contents, format, source_name)?;
+
+        let decoded = if has_extends(&value) || has_includes(&value) {
+            let merged = resolve_includes(value, format, source_name, &SystemEnv)
+                .and_then(|resolved| resolve_extends(resolved, format, source_name, &SystemEnv))?;
+            // NOTE: Errors can't be located in the merged config, so they're
+            // looked for in the extending file.
+            serde_json::from_value(merged)
+                .map_err(|error| format_json_error(source_name, contents, &error))
+        } else {
+            // Deserialize the contents directly in order to report the
+            // locations of any errors.
+            match format {
+                ConfigFormat::Json => serde_json::from_str(contents)
+                    .map_err(|error| format_json_error(source_name, contents, &error)),
+                ConfigFormat::Toml => toml::from_str(contents)
+                    .map_err(|error| format_toml_error(source_name, contents, &error)),
+                ConfigFormat::Yaml => serde_yaml::from_str(contents)
+                    .map_err(|error| format_yaml_error(source_name, contents, &error)),
+            }
+        };
+
+        let mut config: Config = decoded?;
+        config
+            .resolve_named_layouts()
+            .map_err(|error| format!("{}: {}", source_name, error))?;
+        config.source_dir = Path::new(source_name)
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(Path::to_path_buf);
+        config.expand_for_each();
+        Ok(config)
+    }
+
+    /// Read a project config (e.g. from stdin). Unlike files, there's no
+    /// extension to infer the format from, so it must be given.
+    pub fn new_from_reader<R: Read>(
+        mut reader: R,
+        format: ConfigFormat,
+        source_name: &str,
+    ) -> Result<Config, String> {
+        let mut contents = String::new();
+        reader
+            .read_to_string(&mut contents)
+            .map_err(|_| String::from("Unable to read config file."))?;
+        Config::from_str_with_format(&contents, format, source_name)
+    }
+
+    pub fn to_format(&self, format: ConfigFormat) -> Result<String, String> {
+        match format {
+            ConfigFormat::Json => serde_json::to_string_pretty(self)
+                .map(|json| json + "\n")
+                .map_err(|error| error.to_string()),
+            ConfigFormat::Toml => self.to_toml(),
+            ConfigFormat::Yaml => serde_yaml::to_string(self).map_err(|error| error.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Layout;
+
+    #[test]
+    fn it_infers_formats_from_extensions() {
+        assert_eq!(
+            Some(ConfigFormat::Json),
+            ConfigFormat::from_path("foo.json")
+        );
+        assert_eq!(
+            Some(ConfigFormat::Toml),
+            ConfigFormat::from_path("foo.toml")
+        );
+        assert_eq!(
+            Some(ConfigFormat::Yaml),
+            ConfigFormat::from_path("a/foo.yaml")
+        );
+        assert_eq!(Some(ConfigFormat::Yaml), ConfigFormat::from_path("foo.YML"));
+        assert_eq!(None, ConfigFormat::from_path("foo"));
+        assert_eq!(None, ConfigFormat::from_path("foo.ini"));
+    }
+
+    #[test]
+    fn it_parses_yaml_and_json_configs() {
+        let yaml = r#"
+name: foo
+layouts:
+  wide: main-vertical
+windows:
+  - name: editor
+    layout: wide
+    panes:
+      - commands: [vim]
+"#;
+        let json = r#"{
+  "name": "foo",
+  "layouts": {"wide": "main-vertical"},
+  "windows": [
+    {"name": "editor", "layout": "wide", "panes": [{"commands": ["vim"]}]}
+  ]
+}"#;
+        for (contents, format) in [(yaml, ConfigFormat::Yaml), (json, ConfigFormat::Json)] {
+            let config = Config::from_str_with_format(contents, format, "foo").unwrap();
+            assert!(config.attached);
+            assert_eq!("tmux", config.terminal_multiplexer);
+            assert_eq!(Some(String::from("editor")), config.windows[0].name);
+            assert_eq!(
+                Some(String::from("main-vertical")),
+                config.windows[0]
+                    .layout
+                    .as_ref()
+                    .map(|layout| layout.to_string())
+            );
+            assert_eq!(vec!["vim"], config.windows[0].panes[0].commands);
+        }
+    }
+
+    #[test]
+    fn it_reads_configs() {
+        let contents = "{\"name\": \"foo\", \"windows\": [{\"name\": \"one\"}]}";
+        let config =
+            Config::new_from_reader(contents.as_bytes(), ConfigFormat::Json, "<stdin>").unwrap();
+        assert_eq!("foo", config.name);
+        assert_eq!(Some(String::from("one")), config.windows[0].name);
+
+        assert_eq!(
+            Err(String::from(
+                "<stdin>:1:5: expected an equals, found a colon\n  |\n1 | name: foo\n  |     ^"
+            )),
+            Config::new_from_reader("name: foo".as_bytes(), ConfigFormat::Toml, "<stdin>")
+                .map(|config| config.name)
+        );
+    }
+
+    #[test]
+    fn it_merges_extended_configs() {
+        let project_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            project_dir.path().join("base.yml"),
+            r#"
+attached: false
+hooks:
+  - name: client-attached
+    command: run-shell 'echo base'
+windows:
+  - name: logs
+    panes:
+      - commands: [tail -f log]
+"#,
+        )
+        .unwrap();
+        let project_path = project_dir.path().join("project.toml");
+        let contents = r#"
+extends = "base.yml"
+name = "foo"
+
+[[windows]]
+  name = "editor"
+[[windows]]
+  name = "logs"
+  [[windows.panes]]
+    commands = ["journalctl -f"]
+"#;
+
+        let config = Config::from_str_with_format(
+            contents,
+            ConfigFormat::Toml,
+            &project_path.display().to_string(),
+        )
+        .unwrap();
+        assert!(!config.attached);
+        assert_eq!("tmux", config.terminal_multiplexer);
+        assert_eq!("run-shell 'echo base'", config.hooks[0].command());
+        assert_eq!(
+            vec![Some(String::from("logs")), Some(String::from("editor"))],
+            config
+                .windows
+                .iter()
+                .map(|window| window.name.clone())
+                .collect::<Vec<Option<String>>>()
+        );
+        assert_eq!(vec!["journalctl -f"], config.windows[0].panes[0].commands);
+    }
+
+    #[test]
+    fn it_round_trips_each_format() {
+        let config = Config::builder("foo")
+            .layout(Layout::Tiled)
+            .window(|window| window.name("editor").pane(|pane| pane.command("vim")))
+            .build()
+            .unwrap();
+        for format in [ConfigFormat::Json, ConfigFormat::Toml, ConfigFormat::Yaml] {
+            let contents = config.to_format(format).unwrap();
+            let decoded = Config::from_str_with_format(&contents, format, "foo").unwrap();
+            assert_eq!(config.to_toml(), decoded.to_toml());
+        }
+    }
+
+    #[test]
+    fn it_writes_yaml_without_unset_options() {
+        let config = Config::builder("foo")
+            .layout(Layout::Tiled)
+            .window(|window| window.pane(|pane| pane.command("vim")))
+            .build()
+            .unwrap();
+        let expected = r#"terminal_multiplexer: tmux
+attached: true
+layout: tiled
+name: foo
+windows:
+- panes:
+  - commands:
+    - vim
+"#;
+        assert_eq!(expected, config.to_format(ConfigFormat::Yaml).unwrap());
+    }
+}
@@ -0,0 +1,50 @@
+/// Whether `value` matches `pattern`, in which `*` matches any characters and
+/// `?` matches any one character (e.g. `work-*` or `ap?`).
+pub fn matches_glob(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let value: Vec<char> = value.chars().collect();
+    let (mut pattern_index, mut value_index) = (0, 0);
+    // The position of the last `*` and of the first value char it hasn't
+    // matched yet, to backtrack to when the rest of the pattern doesn't match.
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while value_index < value.len() {
+        match pattern.get(pattern_index) {
+            Some('*') => {
+                backtrack = Some((pattern_index, value_index));
+                pattern_index += 1;
+            }
+            Some(c) if *c == '?' || *c == value[value_index] => {
+                pattern_index += 1;
+                value_index += 1;
+            }
+            _ => match backtrack {
+                Some((star_index, star_value_index)) => {
+                    backtrack = Some((star_index, star_value_index + 1));
+                    pattern_index = star_index + 1;
+                    value_index = star_value_index + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[pattern_index..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_matches_globs() {
+        assert!(matches_glob("work-*", "work-laptop"));
+        assert!(matches_glob("ap?", "api"));
+        assert!(matches_glob("a.b", "a.b"));
+        assert!(matches_glob("*", ""));
+        assert!(matches_glob("*-*-db", "eu-west-1-db"));
+        assert!(!matches_glob("a.b", "axb"));
+        assert!(!matches_glob("work", "work-laptop"));
+        assert!(!matches_glob("ap?", "apis"));
+        assert!(!matches_glob("*-db", "db"));
+    }
+}
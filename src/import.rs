@@ -0,0 +1,826 @@
+use crate::{
+    write_config_output, Config, ConfigDefaultValues, Hook, HookName, Layout, Pane, Window,
+};
+use serde_yaml::{Mapping, Value};
+use std::error::Error;
+use std::fs::File;
+use std::io::prelude::*;
+
+// Conversion of other tools' project files into rmuxinator Configs.
+// Anything which can't be translated (or can only be approximated) is
+// reported back to the caller as a warning rather than silently dropped.
+
+type Warnings = Vec<String>;
+
+/// The user option which imported pane names are stored in, since (unlike
+/// tmuxinator, which sets pane titles) names are only kept if it's set.
+const PANE_NAME_USER_OPTION: &str = "pane_name";
+
+fn build_run_shell_command(command: &str) -> String {
+    format!(
+        "run-shell \"{}\"",
+        command.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+fn build_select_window_hook(session_name: &str, window_name: &str) -> Hook {
+    Hook {
+        command: format!("select-window -t \"{}:{}\"", session_name, window_name),
+        name: HookName::ClientAttached,
+    }
+}
+
+fn value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(value_) => Some(value_.clone()),
+        Value::Number(value_) => Some(value_.to_string()),
+        Value::Bool(value_) => Some(value_.to_string()),
+        _ => None,
+    }
+}
+
+fn value_to_commands(value: &Value, context: &str, warnings: &mut Warnings) -> Vec<String> {
+    match value {
+        Value::Null => vec![],
+        Value::Sequence(values) => values
+            .iter()
+            .flat_map(|value_| value_to_commands(value_, context, warnings))
+            .collect(),
+        _ => match value_to_string(value) {
+            Some(command) => vec![command],
+            None => {
+                warnings.push(format!("{}: unable to translate commands", context));
+                vec![]
+            }
+        },
+    }
+}
+
+fn mapping_get<'a>(mapping: &'a Mapping, key: &str) -> Option<&'a Value> {
+    mapping.get(Value::String(String::from(key)))
+}
+
+fn mapping_keys(mapping: &Mapping) -> Vec<String> {
+    mapping.keys().filter_map(value_to_string).collect()
+}
+
+fn build_tmuxinator_pane(
+    value: &Value,
+    pre_commands: &[String],
+    context: &str,
+    warnings: &mut Warnings,
+) -> Pane {
+    // Panes are either a command, a list of commands or (in tmuxinator >= 3)
+    // a single-entry hash of pane name => command(s).
+    let (name, commands) = match value {
+        Value::Mapping(mapping) if mapping.len() == 1 => {
+            let (name, commands) = mapping.iter().next().unwrap();
+            (
+                value_to_string(name),
+                value_to_commands(commands, context, warnings),
+            )
+        }
+        _ => (None, value_to_commands(value, context, warnings)),
+    };
+
+    let mut pane_commands = pre_commands.to_vec();
+    pane_commands.extend(commands);
+
+    Pane {
+        commands: pane_commands,
+        name,
+        ..Pane::default()
+    }
+}
+
+fn build_tmuxinator_window(
+    value: &Value,
+    pre_window_commands: &[String],
+    context: &str,
+    warnings: &mut Warnings,
+) -> Window {
+    // Windows are single-entry hashes of window name => definition, where the
+    // definition is either a command, a list of commands or a hash of options.
+    let (name, definition) = match value {
+        Value::Mapping(mapping) if mapping.len() == 1 => {
+            let (name, definition) = mapping.iter().next().unwrap();
+            (value_to_string(name), definition.clone())
+        }
+        _ => {
+            warnings.push(format!(
+                "{}: expected a hash of window name => window definition",
+                context
+            ));
+            (None, Value::Null)
+        }
+    };
+
+    let options = match definition {
+        Value::Mapping(options) => options,
+        definition_ => {
+            let pane = build_tmuxinator_pane(&definition_, pre_window_commands, context, warnings);
+            return Window {
+                name,
+                panes: vec![pane],
+                ..Window::default()
+            };
+        }
+    };
+
+    let mut pre_commands = pre_window_commands.to_vec();
+    if let Some(pre) = mapping_get(&options, "pre") {
+        pre_commands.extend(value_to_commands(pre, context, warnings));
+    }
+
+    let mut window = Window {
+        name,
+        ..Window::default()
+    };
+
+    for key in mapping_keys(&options) {
+        let value = mapping_get(&options, &key).unwrap();
+        match key.as_str() {
+            "layout" => {
+                window.layout = value_to_string(value).map(Layout::Custom);
+            }
+            "root" => {
+                window.start_directory = value_to_string(value);
+            }
+            "panes" => {
+                let panes = match value {
+                    Value::Sequence(panes) => panes.clone(),
+                    _ => vec![value.clone()],
+                };
+                for (pane_index, pane) in panes.iter().enumerate() {
+                    window.panes.push(build_tmuxinator_pane(
+                        pane,
+                        &pre_commands,
+                        &format!("{}.panes[{}]", context, pane_index),
+                        warnings,
+                    ));
+                }
+            }
+            "pre" => (),
+            _ => warnings.push(format!("{}.{}: unsupported window option", context, key)),
+        }
+    }
+
+    if window.panes.is_empty() {
+        window.panes.push(Pane {
+            commands: pre_commands,
+            ..Pane::default()
+        });
+    }
+
+    window
+}
+
+/// Convert the contents of a tmuxinator project file into a Config. Any
+/// warnings about settings which could not be (faithfully) translated are
+/// returned alongside it.
+pub fn import_tmuxinator(contents: &str) -> Result<(Config, Vec<String>), String> {
+    let mut warnings = vec![];
+
+    if contents.contains("<%") {
+        warnings.push(String::from(
+            "ERB tags are not evaluated and have been imported verbatim",
+        ));
+    }
+
+    let project: Mapping = serde_yaml::from_str(contents).map_err(|error| error.to_string())?;
+
+    let name = mapping_get(&project, "name")
+        .or_else(|| mapping_get(&project, "project_name"))
+        .and_then(value_to_string)
+        .ok_or_else(|| String::from("missing field `name`"))?;
+
+    let pre_window_commands = match mapping_get(&project, "pre_window") {
+        Some(pre_window) => value_to_commands(pre_window, "pre_window", &mut warnings),
+        None => vec![],
+    };
+
+    let mut config = Config {
+        attached: ConfigDefaultValues::default_as_true(),
+        name: name.clone(),
+        terminal_multiplexer: ConfigDefaultValues::default_as_tmux(),
+        ..Config::default()
+    };
+    let mut tmux_options = vec![];
+
+    for key in mapping_keys(&project) {
+        let value = mapping_get(&project, &key).unwrap();
+        match key.as_str() {
+            "name" | "project_name" | "pre_window" => (),
+            "root" | "project_root" => {
+                config.start_directory = value_to_string(value);
+            }
+            "attach" => {
+                config.attached = value.as_bool().unwrap_or(true);
+            }
+            "tmux_options" | "cli_args" => {
+                tmux_options.extend(value_to_string(value));
+            }
+            "socket_name" => {
+                tmux_options.extend(
+                    value_to_string(value).map(|socket_name| format!("-L {}", socket_name)),
+                );
+            }
+            "startup_window" => {
+                if let Some(startup_window) = value_to_string(value) {
+                    warnings.push(String::from(
+                        "startup_window: approximated by a client-attached hook, which selects the window on every attach",
+                    ));
+                    config
+                        .hooks
+                        .push(build_select_window_hook(&name, &startup_window));
+                }
+            }
+            "on_project_start" | "on_project_exit" | "on_project_stop" => {
+                // These run outside of tmux, so the closest equivalents are
+                // run-shell hooks on the session's lifecycle events.
+                let hook_name = match key.as_str() {
+                    "on_project_start" => HookName::ClientAttached,
+                    "on_project_exit" => HookName::ClientDetached,
+                    _ => HookName::SessionClosed,
+                };
+                warnings.push(format!("{}: approximated by a {} hook", key, hook_name));
+                for command in value_to_commands(value, &key, &mut warnings) {
+                    config.hooks.push(Hook {
+                        command: build_run_shell_command(&command),
+                        name: hook_name.clone(),
+                    });
+                }
+            }
+            "windows" | "tabs" => {
+                if let Value::Sequence(windows) = value {
+                    for (window_index, window) in windows.iter().enumerate() {
+                        config.windows.push(build_tmuxinator_window(
+                            window,
+                            &pre_window_commands,
+                            &format!("windows[{}]", window_index),
+                            &mut warnings,
+                        ));
+                    }
+                } else {
+                    warnings.push(String::from("windows: expected a list of windows"));
+                }
+            }
+            _ => warnings.push(format!("{}: unsupported project option", key)),
+        }
+    }
+
+    if !tmux_options.is_empty() {
+        config.tmux_options = Some(tmux_options.join(" "));
+    }
+
+    let has_pane_names = config
+        .windows
+        .iter()
+        .any(|window| window.panes.iter().any(|pane| pane.name.is_some()));
+    if has_pane_names {
+        warnings.push(format!(
+            "pane names are stored in the @{} user option rather than as pane titles",
+            PANE_NAME_USER_OPTION
+        ));
+        config.pane_name_user_option = Some(String::from(PANE_NAME_USER_OPTION));
+    }
+
+    Ok((config, warnings))
+}
+
+fn build_tmuxp_set_option_hooks(
+    options: &Value,
+    set_option_command: &str,
+    context: &str,
+    warnings: &mut Warnings,
+) -> Vec<Hook> {
+    // There's no first-class support for tmux options, so the closest
+    // equivalent is (re)applying them whenever a client attaches.
+    let options_ = match options {
+        Value::Mapping(options_) => options_,
+        _ => {
+            warnings.push(format!("{}: expected a hash of options", context));
+            return vec![];
+        }
+    };
+    warnings.push(format!(
+        "{}: approximated by a client-attached hook",
+        context
+    ));
+    mapping_keys(options_)
+        .iter()
+        .filter_map(|key| {
+            let value = value_to_string(mapping_get(options_, key).unwrap());
+            if value.is_none() {
+                warnings.push(format!("{}.{}: unable to translate option", context, key));
+            }
+            value.map(|value_| Hook {
+                command: format!("{} {} \"{}\"", set_option_command, key, value_),
+                name: HookName::ClientAttached,
+            })
+        })
+        .collect()
+}
+
+fn value_to_tmuxp_commands(value: &Value, context: &str, warnings: &mut Warnings) -> Vec<String> {
+    // Commands are strings, lists of strings or (in newer versions of tmuxp)
+    // lists of hashes with a `cmd` key.
+    match value {
+        Value::Sequence(values) => values
+            .iter()
+            .flat_map(|value_| match value_ {
+                Value::Mapping(command) => match mapping_get(command, "cmd") {
+                    Some(cmd) => value_to_commands(cmd, context, warnings),
+                    None => {
+                        warnings.push(format!("{}: expected a `cmd` key", context));
+                        vec![]
+                    }
+                },
+                _ => value_to_commands(value_, context, warnings),
+            })
+            .collect(),
+        _ => value_to_commands(value, context, warnings),
+    }
+}
+
+fn build_tmuxp_pane(
+    value: &Value,
+    shell_command_before: &[String],
+    context: &str,
+    warnings: &mut Warnings,
+) -> Pane {
+    let mut pane = Pane {
+        commands: shell_command_before.to_vec(),
+        ..Pane::default()
+    };
+
+    let options = match value {
+        Value::Mapping(options) => options,
+        // tmuxp uses these (as well as null) to mean an empty pane.
+        Value::String(marker) if ["", "blank", "pane"].contains(&marker.as_str()) => {
+            return pane;
+        }
+        _ => {
+            pane.commands
+                .extend(value_to_tmuxp_commands(value, context, warnings));
+            return pane;
+        }
+    };
+
+    for key in mapping_keys(options) {
+        let value_ = mapping_get(options, &key).unwrap();
+        match key.as_str() {
+            "shell_command" => {
+                pane.commands
+                    .extend(value_to_tmuxp_commands(value_, context, warnings));
+            }
+            "start_directory" => {
+                pane.start_directory = value_to_string(value_);
+            }
+            "focus" if value_.as_bool() != Some(true) => (),
+            _ => warnings.push(format!("{}.{}: unsupported pane option", context, key)),
+        }
+    }
+
+    pane
+}
+
+fn build_tmuxp_window(
+    value: &Value,
+    session_name: &str,
+    shell_command_before: &[String],
+    context: &str,
+    warnings: &mut Warnings,
+) -> (Window, Vec<Hook>) {
+    let mut window = Window::default();
+    let mut hooks = vec![];
+
+    let options = match value {
+        Value::Mapping(options) => options,
+        _ => {
+            warnings.push(format!("{}: expected a hash of window options", context));
+            return (window, hooks);
+        }
+    };
+
+    window.name = mapping_get(options, "window_name").and_then(value_to_string);
+
+    let mut pane_commands_before = shell_command_before.to_vec();
+    if let Some(window_shell_command_before) = mapping_get(options, "shell_command_before") {
+        pane_commands_before.extend(value_to_tmuxp_commands(
+            window_shell_command_before,
+            context,
+            warnings,
+        ));
+    }
+
+    for key in mapping_keys(options) {
+        let value_ = mapping_get(options, &key).unwrap();
+        match key.as_str() {
+            "window_name" | "shell_command_before" => (),
+            "layout" => {
+                window.layout = value_to_string(value_).map(Layout::Custom);
+            }
+            "start_directory" => {
+                window.start_directory = value_to_string(value_);
+            }
+            "focus" => {
+                if value_.as_bool() == Some(true) {
+                    match &window.name {
+                        Some(window_name) => {
+                            warnings.push(format!(
+                                "{}.focus: approximated by a client-attached hook, which selects the window on every attach",
+                                context
+                            ));
+                            hooks.push(build_select_window_hook(session_name, window_name));
+                        }
+                        None => warnings.push(format!(
+                            "{}.focus: unable to focus a window without a window_name",
+                            context
+                        )),
+                    }
+                }
+            }
+            "options" => match &window.name {
+                Some(window_name) => hooks.extend(build_tmuxp_set_option_hooks(
+                    value_,
+                    &format!("set-window-option -t \"{}:{}\"", session_name, window_name),
+                    &format!("{}.options", context),
+                    warnings,
+                )),
+                None => warnings.push(format!(
+                    "{}.options: unable to set options on a window without a window_name",
+                    context
+                )),
+            },
+            "panes" => {
+                if let Value::Sequence(panes) = value_ {
+                    for (pane_index, pane) in panes.iter().enumerate() {
+                        window.panes.push(build_tmuxp_pane(
+                            pane,
+                            &pane_commands_before,
+                            &format!("{}.panes[{}]", context, pane_index),
+                            warnings,
+                        ));
+                    }
+                } else {
+                    warnings.push(format!("{}.panes: expected a list of panes", context));
+                }
+            }
+            _ => warnings.push(format!("{}.{}: unsupported window option", context, key)),
+        }
+    }
+
+    if window.panes.is_empty() {
+        window.panes.push(Pane {
+            commands: pane_commands_before,
+            ..Pane::default()
+        });
+    }
+
+    (window, hooks)
+}
+
+/// Convert the contents of a tmuxp workspace file (YAML or JSON) into a
+/// Config. Any warnings about settings which could not be (faithfully)
+/// translated are returned alongside it.
+pub fn import_tmuxp(contents: &str) -> Result<(Config, Vec<String>), String> {
+    let mut warnings = vec![];
+
+    // NOTE: JSON is (for all practical purposes) a subset of YAML, so both
+    // formats can be handled by the YAML parser.
+    let workspace: Mapping = serde_yaml::from_str(contents).map_err(|error| error.to_string())?;
+
+    let name = mapping_get(&workspace, "session_name")
+        .and_then(value_to_string)
+        .ok_or_else(|| String::from("missing field `session_name`"))?;
+
+    let shell_command_before = match mapping_get(&workspace, "shell_command_before") {
+        Some(shell_command_before_) => {
+            value_to_tmuxp_commands(shell_command_before_, "shell_command_before", &mut warnings)
+        }
+        None => vec![],
+    };
+
+    let mut config = Config {
+        attached: ConfigDefaultValues::default_as_true(),
+        name: name.clone(),
+        terminal_multiplexer: ConfigDefaultValues::default_as_tmux(),
+        ..Config::default()
+    };
+
+    for key in mapping_keys(&workspace) {
+        let value = mapping_get(&workspace, &key).unwrap();
+        match key.as_str() {
+            "session_name" | "shell_command_before" => (),
+            "start_directory" => {
+                config.start_directory = value_to_string(value);
+            }
+            "options" => {
+                config.hooks.extend(build_tmuxp_set_option_hooks(
+                    value,
+                    &format!("set-option -t \"{}\"", name),
+                    "options",
+                    &mut warnings,
+                ));
+            }
+            "windows" => {
+                if let Value::Sequence(windows) = value {
+                    for (window_index, window) in windows.iter().enumerate() {
+                        let (window_, hooks) = build_tmuxp_window(
+                            window,
+                            &name,
+                            &shell_command_before,
+                            &format!("windows[{}]", window_index),
+                            &mut warnings,
+                        );
+                        config.windows.push(window_);
+                        config.hooks.extend(hooks);
+                    }
+                } else {
+                    warnings.push(String::from("windows: expected a list of windows"));
+                }
+            }
+            _ => warnings.push(format!("{}: unsupported workspace option", key)),
+        }
+    }
+
+    Ok((config, warnings))
+}
+
+pub fn run_import(
+    source_format: &str,
+    source_path: &str,
+    output_path: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    let mut contents = String::new();
+    File::open(source_path)
+        .map_err(|_| String::from("Unable to open project file."))?
+        .read_to_string(&mut contents)
+        .map_err(|_| String::from("Unable to read project file."))?;
+
+    let (config, warnings) = match source_format {
+        "tmuxinator" => import_tmuxinator(&contents)?,
+        "tmuxp" => import_tmuxp(&contents)?,
+        _ => return Err(format!("Unsupported import format: {}", source_format).into()),
+    };
+
+    for warning in warnings {
+        eprintln!("Warning: {}", warning);
+    }
+
+    write_config_output(&config, None, output_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_imports_a_tmuxinator_project() {
+        let contents = r#"
+name: sample
+root: ~/sample
+socket_name: foo
+tmux_options: -f ~/.tmux.mac.conf
+pre_window: nvm use
+attach: false
+windows:
+  - editor:
+      layout: main-vertical
+      root: ~/sample/editor
+      panes:
+        - vim
+        - - git fetch
+          - git status
+        - logs: tail -f log/development.log
+  - server: bundle exec rails s
+  - blank:
+"#;
+        let (config, warnings) = import_tmuxinator(contents).unwrap();
+
+        assert_eq!(
+            vec![String::from(
+                "pane names are stored in the @pane_name user option rather than as pane titles"
+            )],
+            warnings
+        );
+        assert_eq!(
+            Some(String::from("pane_name")),
+            config.pane_name_user_option
+        );
+        assert_eq!("sample", config.name);
+        assert!(!config.attached);
+        assert_eq!(Some(String::from("~/sample")), config.start_directory);
+        assert_eq!(
+            Some(String::from("-L foo -f ~/.tmux.mac.conf")),
+            config.tmux_options
+        );
+
+        let editor = &config.windows[0];
+        assert_eq!(Some(String::from("editor")), editor.name);
+        assert_eq!("main-vertical", editor.layout.clone().unwrap().to_string());
+        assert_eq!(
+            Some(String::from("~/sample/editor")),
+            editor.start_directory
+        );
+        assert_eq!(vec!["nvm use", "vim"], editor.panes[0].commands);
+        assert_eq!(
+            vec!["nvm use", "git fetch", "git status"],
+            editor.panes[1].commands
+        );
+        assert_eq!(Some(String::from("logs")), editor.panes[2].name);
+        assert_eq!(
+            vec!["nvm use", "tail -f log/development.log"],
+            editor.panes[2].commands
+        );
+
+        let server = &config.windows[1];
+        assert_eq!(
+            vec!["nvm use", "bundle exec rails s"],
+            server.panes[0].commands
+        );
+
+        let blank = &config.windows[2];
+        assert_eq!(vec!["nvm use"], blank.panes[0].commands);
+    }
+
+    #[test]
+    fn it_imports_tmuxinator_lifecycle_hooks() {
+        let contents = r#"
+name: sample
+startup_window: editor
+on_project_exit: echo "bye"
+windows:
+  - editor: vim
+"#;
+        let (config, warnings) = import_tmuxinator(contents).unwrap();
+
+        assert_eq!(2, warnings.len());
+        assert_eq!("client-attached", config.hooks[0].name.to_string());
+        assert_eq!(
+            "select-window -t \"sample:editor\"",
+            config.hooks[0].command
+        );
+        assert_eq!("client-detached", config.hooks[1].name.to_string());
+        assert_eq!("run-shell \"echo \\\"bye\\\"\"", config.hooks[1].command);
+    }
+
+    #[test]
+    fn it_warns_about_untranslatable_tmuxinator_settings() {
+        let contents = r#"
+name: sample
+root: ~/<%= @settings["project"] %>
+on_project_first_start: bundle install
+windows:
+  - editor:
+      synchronize: after
+      panes:
+        - vim
+"#;
+        let (_, warnings) = import_tmuxinator(contents).unwrap();
+        let expected = vec![
+            String::from("ERB tags are not evaluated and have been imported verbatim"),
+            String::from("on_project_first_start: unsupported project option"),
+            String::from("windows[0].synchronize: unsupported window option"),
+        ];
+        assert_eq!(expected, warnings);
+    }
+
+    #[test]
+    fn it_imports_a_tmuxp_workspace() {
+        let contents = r#"
+session_name: workspace
+start_directory: ~/workspace
+shell_command_before:
+  - source .venv/bin/activate
+windows:
+  - window_name: editor
+    layout: main-horizontal
+    start_directory: ~/workspace/src
+    shell_command_before: cd src
+    panes:
+      - vim
+      - shell_command:
+          - cmd: git fetch
+          - git status
+        start_directory: /tmp
+        focus: false
+      - null
+      - blank
+      - pane
+      - ""
+  - window_name: server
+    panes:
+      - shell_command: python -m http.server
+"#;
+        let (config, warnings) = import_tmuxp(contents).unwrap();
+
+        assert!(warnings.is_empty());
+        assert_eq!("workspace", config.name);
+        assert_eq!(Some(String::from("~/workspace")), config.start_directory);
+
+        let editor = &config.windows[0];
+        assert_eq!(Some(String::from("editor")), editor.name);
+        assert_eq!(
+            "main-horizontal",
+            editor.layout.clone().unwrap().to_string()
+        );
+        assert_eq!(
+            Some(String::from("~/workspace/src")),
+            editor.start_directory
+        );
+        assert_eq!(
+            vec!["source .venv/bin/activate", "cd src", "vim"],
+            editor.panes[0].commands
+        );
+        assert_eq!(
+            vec![
+                "source .venv/bin/activate",
+                "cd src",
+                "git fetch",
+                "git status"
+            ],
+            editor.panes[1].commands
+        );
+        assert_eq!(Some(String::from("/tmp")), editor.panes[1].start_directory);
+        assert_eq!(6, editor.panes.len());
+        for blank_pane in &editor.panes[2..] {
+            assert_eq!(
+                vec!["source .venv/bin/activate", "cd src"],
+                blank_pane.commands
+            );
+        }
+
+        let server = &config.windows[1];
+        assert_eq!(
+            vec!["source .venv/bin/activate", "python -m http.server"],
+            server.panes[0].commands
+        );
+    }
+
+    #[test]
+    fn it_imports_tmuxp_focus_and_options_as_hooks() {
+        let contents = r#"{
+  "session_name": "workspace",
+  "options": {"status": "off"},
+  "windows": [
+    {
+      "window_name": "editor",
+      "focus": true,
+      "options": {"automatic-rename": "on"},
+      "panes": [{"shell_command": "vim", "focus": true}]
+    }
+  ]
+}"#;
+        let (config, warnings) = import_tmuxp(contents).unwrap();
+        let commands: Vec<String> = config
+            .hooks
+            .iter()
+            .map(|hook| hook.command.clone())
+            .collect();
+
+        assert_eq!(
+            vec![
+                String::from("set-option -t \"workspace\" status \"off\""),
+                String::from("select-window -t \"workspace:editor\""),
+                String::from("set-window-option -t \"workspace:editor\" automatic-rename \"on\""),
+            ],
+            commands
+        );
+        assert_eq!(
+            Some(&String::from(
+                "windows[0].panes[0].focus: unsupported pane option"
+            )),
+            warnings.last()
+        );
+    }
+
+    #[test]
+    fn it_warns_about_untranslatable_tmuxp_settings() {
+        let contents = r#"
+session_name: workspace
+before_script: ./bootstrap.sh
+windows:
+  - window_name: editor
+    window_index: 5
+    panes:
+      - vim
+"#;
+        let (_, warnings) = import_tmuxp(contents).unwrap();
+        let expected = vec![
+            String::from("before_script: unsupported workspace option"),
+            String::from("windows[0].window_index: unsupported window option"),
+        ];
+        assert_eq!(expected, warnings);
+    }
+
+    #[test]
+    fn it_rejects_a_tmuxinator_project_without_a_name() {
+        let actual = import_tmuxinator("windows: []");
+        assert_eq!(
+            Err(String::from("missing field `name`")),
+            actual.map(|_| ())
+        );
+    }
+}
@@ -0,0 +1,280 @@
+use crate::extends::{
+    canonicalize_config_path, describe_cycle, load_config_value, resolve_config_path,
+};
+use crate::format::ConfigFormat;
+use crate::EnvProvider;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+// Support for `include = "snippets/logs-window.toml"` in windows and panes,
+// which fills in the window or pane from a snippet file. Like `extends`,
+// includes are resolved before the config is deserialized.
+
+const INCLUDE_KEY: &str = "include";
+const INCLUDED_FILE: &str = "included file";
+
+#[derive(Clone, Copy)]
+enum Snippet {
+    Window,
+    Pane,
+}
+
+fn includes_any(values: Option<&Value>, nested_key: Option<&str>) -> bool {
+    values.and_then(Value::as_array).is_some_and(|values_| {
+        values_.iter().any(|value| {
+            value.get(INCLUDE_KEY).is_some()
+                || nested_key.is_some_and(|key| includes_any(value.get(key), None))
+        })
+    })
+}
+
+/// The path of `key` in the value at `path` (which is empty for the top level
+/// of a snippet), e.g. `windows[0].include`.
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        String::from(key)
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+pub fn has_includes(value: &Value) -> bool {
+    includes_any(value.get("windows"), Some("panes"))
+}
+
+/// Resolve the includes in a project config's windows and panes. Paths are
+/// relative to `source_name` (or the config dir).
+pub fn resolve_includes(
+    value: Value,
+    format: ConfigFormat,
+    source_name: &str,
+    env: &dyn EnvProvider,
+) -> Result<Value, String> {
+    let mut object: Map<String, Value> = match value {
+        Value::Object(object_) => object_,
+        _ => return Ok(value),
+    };
+    if let Some(Value::Array(windows)) = object.get_mut("windows") {
+        let mut chain: Vec<PathBuf> = Path::new(source_name).canonicalize().into_iter().collect();
+        for (window_index, window) in windows.iter_mut().enumerate() {
+            *window = resolve_snippet(
+                window.take(),
+                Snippet::Window,
+                format,
+                source_name,
+                &format!("windows[{}]", window_index),
+                env,
+                &mut chain,
+            )?;
+        }
+    }
+    Ok(Value::Object(object))
+}
+
+/// Fill in a window or pane (at `path` in `source_name`) from the snippet it
+/// includes, if any. Values which are set alongside `include` override the
+/// snippet's.
+fn resolve_snippet(
+    value: Value,
+    snippet: Snippet,
+    format: ConfigFormat,
+    source_name: &str,
+    path: &str,
+    env: &dyn EnvProvider,
+    chain: &mut Vec<PathBuf>,
+) -> Result<Value, String> {
+    let mut object: Map<String, Value> = match value {
+        Value::Object(object_) => object_,
+        _ => return Ok(value),
+    };
+
+    if let Snippet::Window = snippet {
+        if let Some(Value::Array(panes)) = object.get_mut("panes") {
+            for (pane_index, pane) in panes.iter_mut().enumerate() {
+                *pane = resolve_snippet(
+                    pane.take(),
+                    Snippet::Pane,
+                    format,
+                    source_name,
+                    &join_path(path, &format!("panes[{}]", pane_index)),
+                    env,
+                    chain,
+                )?;
+            }
+        }
+    }
+
+    let include = match object.remove(INCLUDE_KEY) {
+        Some(Value::String(include_)) => include_,
+        Some(_) => {
+            return Err(format!(
+                "{}: {} must be a string",
+                source_name,
+                join_path(path, INCLUDE_KEY)
+            ))
+        }
+        None => return Ok(Value::Object(object)),
+    };
+    let include_path = join_path(path, INCLUDE_KEY);
+    let describe_error = |error: String| format!("{}: {}: {}", source_name, include_path, error);
+
+    let snippet_path =
+        resolve_config_path(&include, INCLUDED_FILE, source_name, env).map_err(describe_error)?;
+    let canonical_snippet_path =
+        canonicalize_config_path(&snippet_path, INCLUDED_FILE).map_err(describe_error)?;
+    if chain.contains(&canonical_snippet_path) {
+        return Err(describe_error(format!(
+            "include cycle detected ({})",
+            describe_cycle(chain, &canonical_snippet_path)
+        )));
+    }
+
+    let snippet_name = snippet_path.display().to_string();
+    let (snippet_value, snippet_format) =
+        load_config_value(&snippet_path, INCLUDED_FILE, format).map_err(describe_error)?;
+    if !snippet_value.is_object() {
+        return Err(describe_error(format!(
+            "{} must contain a table",
+            snippet_name
+        )));
+    }
+
+    chain.push(canonical_snippet_path);
+    // The snippet's own includes are relative to the snippet.
+    let snippet_value = resolve_snippet(
+        snippet_value,
+        snippet,
+        snippet_format,
+        &snippet_name,
+        "",
+        env,
+        chain,
+    )
+    .map_err(describe_error)?;
+    chain.pop();
+
+    match snippet_value {
+        Value::Object(mut snippet_object) => {
+            snippet_object.extend(object);
+            Ok(Value::Object(snippet_object))
+        }
+        _ => Ok(Value::Object(object)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{write_file, MockEnv};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    #[test]
+    fn it_detects_includes() {
+        assert!(!has_includes(&serde_json::json!({"name": "foo"})));
+        assert!(has_includes(
+            &serde_json::json!({"windows": [{"include": "logs.toml"}]})
+        ));
+        assert!(has_includes(
+            &serde_json::json!({"windows": [{"panes": [{"include": "psql.toml"}]}]})
+        ));
+    }
+
+    #[test]
+    fn it_resolves_window_and_pane_includes() {
+        let project_dir = TempDir::new().unwrap();
+        write_file(
+            project_dir.path(),
+            "snippets/logs.toml",
+            "name = \"logs\"\nlayout = \"tiled\"\n[[panes]]\n  include = \"tail.yml\"\n",
+        );
+        write_file(
+            project_dir.path(),
+            "snippets/tail.yml",
+            "commands: [tail -f log]\n",
+        );
+        write_file(
+            project_dir.path(),
+            "snippets/psql.toml",
+            "name = \"psql\"\ncommands = [\"psql\"]\n",
+        );
+        let project_path = write_file(project_dir.path(), "project.toml", "");
+
+        let value = serde_json::json!({
+            "name": "foo",
+            "windows": [
+                {"include": "snippets/logs.toml", "layout": "even-vertical"},
+                {"panes": [{"include": "snippets/psql.toml", "commands": ["psql db"]}]}
+            ]
+        });
+        let expected = serde_json::json!({
+            "name": "foo",
+            "windows": [
+                {
+                    "name": "logs",
+                    "layout": "even-vertical",
+                    "panes": [{"commands": ["tail -f log"]}]
+                },
+                {"panes": [{"name": "psql", "commands": ["psql db"]}]}
+            ]
+        });
+        let actual = resolve_includes(
+            value,
+            ConfigFormat::Toml,
+            &project_path,
+            &MockEnv(HashMap::new()),
+        )
+        .unwrap();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn it_detects_include_cycles() {
+        let project_dir = TempDir::new().unwrap();
+        write_file(
+            project_dir.path(),
+            "one.toml",
+            "[[panes]]\n  include = \"two.toml\"\n",
+        );
+        write_file(project_dir.path(), "two.toml", "include = \"one.toml\"\n");
+        let project_path = write_file(project_dir.path(), "project.toml", "");
+
+        let value = serde_json::json!({"windows": [{"include": "one.toml"}]});
+        let actual = resolve_includes(
+            value,
+            ConfigFormat::Toml,
+            &project_path,
+            &MockEnv(HashMap::new()),
+        )
+        .unwrap_err();
+        assert!(
+            actual.starts_with(&format!("{}: windows[0].include: ", project_path)),
+            "{}",
+            actual
+        );
+        assert!(actual.contains("include cycle detected"), "{}", actual);
+    }
+
+    #[test]
+    fn it_reports_missing_includes_with_the_including_file() {
+        let project_dir = TempDir::new().unwrap();
+        let project_path = write_file(project_dir.path(), "project.toml", "");
+
+        let value = serde_json::json!({"windows": [{"panes": [{}, {"include": "psql.toml"}]}]});
+        let actual = resolve_includes(
+            value,
+            ConfigFormat::Toml,
+            &project_path,
+            &MockEnv(HashMap::new()),
+        )
+        .unwrap_err();
+        assert_eq!(
+            format!(
+                "{}: windows[0].panes[1].include: Unable to find included file \"psql.toml\" (looked for {})",
+                project_path,
+                project_dir.path().join("psql.toml").display()
+            ),
+            actual
+        );
+    }
+}
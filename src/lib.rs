@@ -1,18 +1,59 @@
 use clap::{App, AppSettings, Arg, SubCommand};
 use derivative::Derivative;
 use regex::Regex;
+use serde::de::IntoDeserializer;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::ffi::OsString;
 use std::fmt;
 use std::fs::File;
 use std::io::prelude::*;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Output};
 use std::str::FromStr;
+use std::sync::OnceLock;
 
 extern crate toml;
 
-// The following TmuxCommandRunner business exists only to facilitate mocking.
+mod backend;
+mod builder;
+mod command;
+mod condition;
+mod export;
+mod extends;
+mod for_each;
+mod format;
+mod glob;
+mod import;
+mod include;
+mod parse_error;
+mod plan;
+mod profile;
+mod runner;
+mod template;
+#[cfg(test)]
+mod test_support;
+mod validate;
+
+use backend::is_screen;
+pub use backend::{MultiplexerBackend, ScreenBackend, TmuxBackend};
+pub use builder::{ConfigBuilder, PaneBuilder, WindowBuilder};
+pub use command::{MultiplexerCommand, OptionScope, ScreenCommand, TmuxCommand};
+pub use condition::{Condition, ConditionEnv};
+
+pub use export::{export_shell_script, export_tmux_script, export_zellij_layout, run_export};
+pub use for_each::ForEach;
+pub use format::ConfigFormat;
+pub use import::{import_tmuxinator, import_tmuxp, run_import};
+use parse_error::suggest;
+pub use plan::{Plan, PlanEnv, PlannedCommand, Provenance};
+pub use runner::{RecordedCommand, RecordingRunner, ScriptedResponse, ScriptedRunner};
+pub use validate::{Diagnostic, Severity};
+
+// The following TmuxCommandRunner business originally existed only to
+// facilitate mocking (it's since become a public extension point).
 // Coming from a dynamic language background, this does not smell right to me
 // but I don't see any way around it.
 // I may yet wind up yanking this out in favor of a different mocking library
@@ -38,7 +79,7 @@ fn binary_exists(name: &str) -> bool {
 }
 
 fn run_tmux_command(
-    terminal_multiplexer: &String,
+    terminal_multiplexer: &str,
     command: &[String],
     wait: bool,
 ) -> Result<Output, Box<dyn Error>> {
@@ -50,7 +91,13 @@ fn run_tmux_command(
     // A better solution would be to introduce something akin to tmuxinator's
     // Doctor class which does basic validation about the environment (tmux, term, etc.)
     // only once at the beginning of the top-level routine.
-    if !binary_exists(terminal_multiplexer) {
+    // NOTE: screen doesn't support -V, so fall back to looking for it on $PATH
+    let compatible = if is_screen(terminal_multiplexer) {
+        test_for_tmux(terminal_multiplexer)
+    } else {
+        binary_exists(terminal_multiplexer)
+    };
+    if !compatible {
         panic!(
             "'{}' does not seem to be a compatible terminal multiplexer",
             terminal_multiplexer
@@ -59,6 +106,14 @@ fn run_tmux_command(
 
     let mut tmux = Command::new(terminal_multiplexer);
     if wait {
+        // NOTE: If the project config was piped in (i.e. `start -`), stdin
+        // isn't a terminal, so give the client the controlling terminal
+        // instead or it won't be able to attach.
+        if !io::stdin().is_terminal() {
+            if let Ok(tty) = File::open("/dev/tty") {
+                tmux.stdin(tty);
+            }
+        }
         let child = tmux.args(command).spawn()?;
         let output: Output = child.wait_with_output()?;
         Ok(output)
@@ -67,21 +122,29 @@ fn run_tmux_command(
     }
 }
 
-trait TmuxCommandRunner {
+/// Runs the commands rmuxinator generates. Implement this to change how (or
+/// where) they're run, e.g. to log them or run them over SSH, and pass it to
+/// run_start_with, run_debug_with or Plan::execute. TmuxWrapper runs them
+/// locally and RecordingRunner and ScriptedRunner are provided for tests.
+pub trait TmuxCommandRunner {
+    /// Run the terminal multiplexer with the given arguments. When wait is
+    /// true (i.e. when attaching), the command should inherit the terminal
+    /// and only return once it exits.
     fn run_tmux_command(
         &self,
-        terminal_multiplexer: &String,
+        terminal_multiplexer: &str,
         command: &[String],
         wait: bool,
     ) -> Result<Output, Box<dyn Error>>;
 }
 
-struct TmuxWrapper;
+/// Runs commands using the local terminal multiplexer.
+pub struct TmuxWrapper;
 
 impl TmuxCommandRunner for TmuxWrapper {
     fn run_tmux_command(
         &self,
-        terminal_multiplexer: &String,
+        terminal_multiplexer: &str,
         command: &[String],
         wait: bool,
     ) -> Result<Output, Box<dyn Error>> {
@@ -89,7 +152,7 @@ impl TmuxCommandRunner for TmuxWrapper {
     }
 }
 
-fn build_pane_args(session_name: &str, window_index: usize) -> Vec<Vec<String>> {
+fn build_pane_args(session_name: &str, window_index: usize) -> Vec<TmuxCommand> {
     // NOTE: Like tmuxinator does, we want to continually apply the tiled layout
     // after splitting and then _only_ at the very end of the window config
     // constructor context apply the specified or inherited layout. This
@@ -102,44 +165,66 @@ fn build_pane_args(session_name: &str, window_index: usize) -> Vec<Vec<String>>
     // See:
     // - https://github.com/ethagnawl/rmuxinator/issues/45
     // - https://web.archive.org/web/20250709171739/https://www.mail-archive.com/tmux-users@googlegroups.com/msg01241.html
+    let target = format!("{}:{}", session_name, window_index);
     vec![
-        vec![
-            String::from("split-window"),
-            String::from("-t"),
-            format!("{}:{}", session_name, window_index.to_string()),
-        ],
-        vec![
-            String::from("select-layout"),
-            String::from("-t"),
-            format!("{}:{}", session_name, window_index.to_string()),
-            String::from("tiled"),
-        ],
+        TmuxCommand::SplitWindow {
+            target: target.clone(),
+        },
+        TmuxCommand::SelectLayout {
+            target,
+            layout: String::from("tiled"),
+        },
     ]
 }
 
-fn build_window_layout_args(
+/// Where a window's layout came from.
+#[derive(Debug, PartialEq)]
+enum LayoutSource {
+    Config,
+    Window,
+    ResponsiveLayout(usize),
+}
+
+fn select_window_layout<'a>(
+    config_layout: &'a Option<Layout>,
+    window_layout: &'a Option<Layout>,
+    window_responsive_layouts: &'a [ResponsiveLayout],
+    terminal_size: &Option<TerminalSize>,
+) -> Option<(&'a Layout, LayoutSource)> {
+    // The first responsive layout whose size conditions match the attaching
+    // terminal wins. Otherwise, fall back to window.layout || config.layout.
+    let responsive_layout = window_responsive_layouts
+        .iter()
+        .enumerate()
+        .find(|(_, responsive_layout)| responsive_layout.matches(terminal_size))
+        .map(|(index, responsive_layout)| {
+            (
+                &responsive_layout.layout,
+                LayoutSource::ResponsiveLayout(index),
+            )
+        });
+
+    responsive_layout
+        .or_else(|| {
+            window_layout
+                .as_ref()
+                .map(|layout| (layout, LayoutSource::Window))
+        })
+        .or_else(|| {
+            config_layout
+                .as_ref()
+                .map(|layout| (layout, LayoutSource::Config))
+        })
+}
+
+fn build_select_layout_args(
     session_name: &str,
     window_index: &usize,
-    config_layout: &Option<Layout>,
-    window_layout: &Option<Layout>,
-) -> Option<Vec<String>> {
-    let maybe_layout = if window_layout.is_some() {
-        &window_layout
-    } else if config_layout.is_some() {
-        &config_layout
-    } else {
-        &None
-    };
-
-    if let Some(layout) = maybe_layout {
-        Some(vec![
-            String::from("select-layout"),
-            String::from("-t"),
-            format!("{}:{}", session_name, window_index.to_string()),
-            layout.to_string(),
-        ])
-    } else {
-        None
+    layout: &Layout,
+) -> TmuxCommand {
+    TmuxCommand::SelectLayout {
+        target: format!("{}:{}", session_name, window_index),
+        layout: layout.to_string(),
     }
 }
 
@@ -148,53 +233,28 @@ fn build_create_window_args(
     window_index: usize,
     window_name: &Option<String>,
     start_directory: &Option<String>,
-) -> Vec<String> {
-    let mut create_window_args = vec![
-        String::from("new-window"),
-        String::from("-t"),
-        format!("{}:{}", session_name, window_index.to_string()),
-    ];
-
-    if let Some(_window_name) = window_name {
-        create_window_args.push(String::from("-n"));
-        create_window_args.push(_window_name.to_string());
+) -> TmuxCommand {
+    TmuxCommand::NewWindow {
+        target: format!("{}:{}", session_name, window_index),
+        window_name: window_name.clone(),
+        start_directory: start_directory.clone(),
     }
-
-    if let Some(start_directory_) = start_directory {
-        create_window_args.push(String::from("-c"));
-        create_window_args.push(String::from(start_directory_));
-    }
-
-    create_window_args
 }
 
 fn build_session_args(
     session_name: &str,
     window_name: Option<String>,
     start_directory: &StartDirectory,
-) -> Vec<String> {
+) -> TmuxCommand {
     // Pass first window name to new-session, otherwise a default window gets
     // created that would need to be killed at a later point. I tried doing
     // this, but saw unexpected behavior -- most likely because the indexes get
     // shuffled.
-    let mut session_args = vec![
-        String::from("new-session"),
-        String::from("-d"),
-        String::from("-s"),
-        String::from(session_name),
-    ];
-
-    if let Some(_window_name) = window_name {
-        session_args.push(String::from("-n"));
-        session_args.push(_window_name);
+    TmuxCommand::NewSession {
+        session_name: String::from(session_name),
+        window_name,
+        start_directory: start_directory.clone(),
     }
-
-    if let Some(start_directory_) = start_directory {
-        session_args.push(String::from("-c"));
-        session_args.push(String::from(start_directory_));
-    }
-
-    session_args
 }
 
 fn build_pane_command_args(
@@ -202,14 +262,11 @@ fn build_pane_command_args(
     window_index: &usize,
     pane_index: &usize,
     command: &str,
-) -> Vec<String> {
-    vec![
-        String::from("send-keys"),
-        String::from("-t"),
-        format!("{}:{}.{}", session_name, window_index, pane_index),
-        String::from(command),
-        String::from("Enter"),
-    ]
+) -> TmuxCommand {
+    TmuxCommand::SendKeys {
+        target: format!("{}:{}.{}", session_name, window_index, pane_index),
+        command: String::from(command),
+    }
 }
 
 trait EnvProvider {
@@ -223,6 +280,14 @@ impl EnvProvider for SystemEnv {
     }
 }
 
+/// Expand a leading `~/` to $HOME.
+fn expand_home(path: &str, env: &dyn EnvProvider) -> PathBuf {
+    match (path.strip_prefix("~/"), env.get_var("HOME")) {
+        (Some(relative_path), Some(home)) => Path::new(&home).join(relative_path),
+        _ => PathBuf::from(path),
+    }
+}
+
 fn in_tmux_context(env: &dyn EnvProvider) -> bool {
     env.get_var("TMUX")
         // NOTE: This is naive but if a value is present and non-sensical
@@ -231,27 +296,19 @@ fn in_tmux_context(env: &dyn EnvProvider) -> bool {
         .unwrap_or(false)
 }
 
-fn build_attach_command_args(session_name: &str) -> Vec<String> {
-    let session_op = if in_tmux_context(&SystemEnv) {
-        String::from("switch-client")
-    } else {
-        String::from("attach-session")
-    };
-    vec![
-        String::from("-u"),
-        String::from(session_op),
-        String::from("-t"),
-        String::from(session_name),
-    ]
+fn build_attach_command_args(session_name: &str) -> TmuxCommand {
+    TmuxCommand::Attach {
+        session_name: String::from(session_name),
+        switch_client: in_tmux_context(&SystemEnv),
+    }
 }
 
-fn build_session_start_directory(config: &Config) -> StartDirectory {
+fn build_session_start_directory(config: &Config, first_window: Option<&Window>) -> StartDirectory {
     // Compute start_directory for session/first window using:
     // window.start_directory || config.start_directory
-    if !config.windows.is_empty() {
-        config.windows[0].start_directory.clone()
-    } else {
-        config.start_directory.clone()
+    match first_window {
+        Some(window) => window.start_directory.clone(),
+        None => config.start_directory.clone(),
     }
 }
 
@@ -277,13 +334,11 @@ fn build_pane_start_directory(
         .or(config_start_directory_)
 }
 
-fn build_hook_args(hook: &Hook) -> Vec<String> {
-    vec![
-        String::from("set-hook"),
-        String::from("-a"),
-        hook.name.to_string(),
-        hook.command.to_string(),
-    ]
+fn build_hook_args(hook: &Hook) -> TmuxCommand {
+    TmuxCommand::SetHook {
+        hook_name: hook.name.to_string(),
+        command: hook.command.to_string(),
+    }
 }
 
 fn build_rename_pane_args(
@@ -292,20 +347,18 @@ fn build_rename_pane_args(
     pane_index: usize,
     pane_name_user_option: &Option<String>,
     pane_name: &Option<String>,
-) -> Option<Vec<String>> {
+) -> Option<TmuxCommand> {
     // requires tmux >= 3.0a and some variation of the following in
     // tmux.conf:
     // e.g. `set -g pane-border-format "#{@user_option}"`
     // TODO: Is it worth sniffing out user option support?
-    if pane_name.is_some() && pane_name_user_option.is_some() {
-        Some(vec![
-            String::from("set-option"),
-            String::from("-p"),
-            String::from("-t"),
-            format!("{}:{}.{}", session_name, window_index, pane_index),
-            format!("@{}", pane_name_user_option.clone().unwrap()),
-            pane_name.clone().unwrap(),
-        ])
+    if let (Some(pane_name_), Some(pane_name_user_option_)) = (pane_name, pane_name_user_option) {
+        Some(TmuxCommand::SetOption {
+            scope: OptionScope::Pane,
+            target: Some(format!("{}:{}.{}", session_name, window_index, pane_index)),
+            option: format!("@{}", pane_name_user_option_),
+            value: pane_name_.clone(),
+        })
     } else {
         None
     }
@@ -324,10 +377,37 @@ pub fn test_for_tmux(tmux_command: &str) -> bool {
     output.status.success()
 }
 
-fn build_commands_with_tmux_options_prefix(
-    tmux_options: String,
-    commands: Vec<(Vec<String>, bool)>,
-) -> Vec<(Vec<String>, bool)> {
+/// The size of a terminal, in columns and rows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TerminalSize {
+    pub width: usize,
+    pub height: usize,
+}
+
+fn parse_terminal_size(stty_output: &str) -> Option<TerminalSize> {
+    // `stty size` prints "<rows> <columns>"
+    let mut parts = stty_output.split_whitespace();
+    let height = parts.next()?.parse::<usize>().ok()?;
+    let width = parts.next()?.parse::<usize>().ok()?;
+    Some(TerminalSize { width, height })
+}
+
+fn get_terminal_size() -> Option<TerminalSize> {
+    // NOTE: stdin/stdout may be redirected (e.g. when piping debug output),
+    // so explicitly ask about the controlling terminal. If there isn't one,
+    // size-constrained responsive layouts simply won't match.
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg("stty size < /dev/tty")
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_terminal_size(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn build_args_with_tmux_options_prefix(tmux_options: &str, args: Vec<String>) -> Vec<String> {
     // NOTE: If the full string (e.g. -f /tmp/custom.conf) is used it results
     // in an error. There's something up with the whitespace or similar which
     // results in the flag being consumed and the shell trying to execute the
@@ -335,21 +415,25 @@ fn build_commands_with_tmux_options_prefix(
     // TODO: Ideally we'd do _some_ amount of validation of these arguments.
     // Right now, anything and everything is being passed through and can/will
     // cause tmux errors.
-    let tmux_options_parts: Vec<&str> = tmux_options.split(" ").collect();
-    let tmux_option_strs: Vec<String> = tmux_options_parts
-        .clone()
-        .into_iter()
-        .map(|x| x.to_string())
-        .collect();
+    // ["new-session", "-d", "-s", "new-sesh", "-n", "one"]
+    // becomes
+    // ["-f", "/tmp/tmux.custom.conf", "new-session", "-d", "-s", "new-sesh", "-n", "one"]
+    let mut command: Vec<String> = tmux_options.split(" ").map(String::from).collect();
+    command.extend(args);
+    command
+}
+
+fn build_commands_with_tmux_options_prefix(
+    tmux_options: String,
+    commands: Vec<(Vec<String>, bool)>,
+) -> Vec<(Vec<String>, bool)> {
     commands
         .into_iter()
-        .map(|(nested_vec, bool)| {
-            // (["new-session", "-d", "-s", "new-sesh", "-n", "one"], false)
-            // becomes
-            // (["-f", "/tmp/tmux.custom.conf", "new-session", "-d", "-s", "new-sesh", "-n", "one"], false)
-            let mut command = tmux_option_strs.clone();
-            command.extend(nested_vec.iter().cloned());
-            (command, bool)
+        .map(|(args, wait)| {
+            (
+                build_args_with_tmux_options_prefix(&tmux_options, args),
+                wait,
+            )
         })
         .collect()
 }
@@ -357,123 +441,17 @@ fn build_commands_with_tmux_options_prefix(
 fn convert_config_to_tmux_commands(
     config: &Config,
     base_indices: TmuxBaseIndices,
+    terminal_size: Option<TerminalSize>,
 ) -> Vec<(Vec<String>, bool)> {
-    // TODO: We should consider adding sensible line endings
-    // to clearly delineate command boundaries.
-    let mut commands = vec![];
-
-    let session_name = &config.name;
-
-    let session_start_directory = build_session_start_directory(&config);
-
-    let first_window = if let Some(window) = config.windows.first() {
-        window.name.clone()
-    } else {
-        None
-    };
-
-    let create_session_args =
-        build_session_args(session_name, first_window, &session_start_directory);
-    commands.push((create_session_args, false));
-
-    for hook in config.hooks.iter() {
-        let hook_command = build_hook_args(&hook);
-        commands.push((hook_command, false));
-    }
-
-    for (window_iterator_index, window) in config.windows.iter().enumerate() {
-        // The "first" window is created by create_session because tmux always
-        // creates a window when creating a session.
-        // The alternative would be to create all of the project windows and
-        // then kill the first/default one, but I saw unexpected behavior
-        // (first window's commands not being run) when attempting that -- I
-        // think it's because the indexes get shuffled.
-        // The alternative approach would be more explicit and preferable, so
-        // maybe it's worth revisiting.
-        let window_index = base_indices.base_index + window_iterator_index;
-        if window_iterator_index > 0 {
-            // TODO: This is heavy handed and this logic is _sort of_ duped
-            // in a few places. Maybe each type should have a method which is
-            // able to compute its own starting directory?
-            let window_start_directory =
-                build_window_start_directory(&config.start_directory, &window.start_directory);
-            let create_window_args = build_create_window_args(
-                session_name,
-                window_index,
-                &window.name,
-                &window_start_directory,
-            );
-
-            commands.push((create_window_args, false));
-        }
-
-        for (pane_iterator_index, pane) in window.panes.iter().enumerate() {
-            let pane_index = base_indices.pane_base_index + pane_iterator_index;
-            // The "first" pane is created by default by the containing window
-            if pane_iterator_index > 0 {
-                let pane_args = build_pane_args(session_name, window_index);
-                for pane_arg in pane_args {
-                    commands.push((pane_arg, false));
-                }
-            }
-
-            // Conditionally set start_directory for pane.
-            // Unfortunately, this can't be done cleanly using create_pane
-            // because pane 0 is created implicitly.
-            let pane_start_directory = build_pane_start_directory(
-                &config.start_directory,
-                &window.start_directory,
-                &pane.start_directory,
-            );
-            if let Some(pane_start_directory) = pane_start_directory {
-                let command = format!("cd {}", pane_start_directory);
-                let pane_command_args =
-                    build_pane_command_args(session_name, &window_index, &pane_index, &command);
-                commands.push((pane_command_args, false));
-            }
-
-            for (_, command) in pane.commands.iter().enumerate() {
-                let pane_command_args =
-                    build_pane_command_args(session_name, &window_index, &pane_index, command);
-                commands.push((pane_command_args, false));
-            }
-
-            let rename_pane_args = build_rename_pane_args(
-                session_name,
-                window_index,
-                pane_index,
-                &config.pane_name_user_option,
-                &pane.name.clone(),
-            );
-            if let Some(rename_pane_args_) = rename_pane_args {
-                commands.push((rename_pane_args_, false));
-            }
-        }
-
-        let window_layout_args =
-            build_window_layout_args(session_name, &window_index, &config.layout, &window.layout);
-
-        if let Some(window_layout_args_) = window_layout_args {
-            commands.push((window_layout_args_, false));
-        }
-    }
-
-    // TODO: It's not ideal that logic for constructing this command lives in
-    // multiple places (i.e. here and in build_attach_command_args)
-    // I think ideally this would be a _tell, don't ask_ situation and we
-    // either validate that the output is valid (i.e. via Option) and let the
-    // function figure out whether or how to compute the command. This is
-    // probably also something we should do for all of these helper functions.
-    if config.attached {
-        let attach_args = build_attach_command_args(&config.name);
-        commands.push((attach_args, true));
-    }
-
-    if let Some(tmux_options) = config.tmux_options.clone() {
-        commands = build_commands_with_tmux_options_prefix(tmux_options, commands);
-    }
-
-    commands
+    let plan = config.plan(&PlanEnv {
+        base_index: base_indices.base_index,
+        pane_base_index: base_indices.pane_base_index,
+        terminal_size,
+        conditions: ConditionEnv::system(),
+    });
+    plan.iter()
+        .map(|planned_command| (plan.args(&planned_command.command), planned_command.wait))
+        .collect()
 }
 
 #[derive(Debug, PartialEq)]
@@ -485,7 +463,15 @@ struct TmuxBaseIndices {
 fn get_tmux_base_indices(
     config: &Config,
     tmux_command_runner: &dyn TmuxCommandRunner,
-) -> TmuxBaseIndices {
+) -> Result<TmuxBaseIndices, Box<dyn Error>> {
+    // screen windows are always numbered from 0 and it has no panes to number
+    if is_screen(&config.terminal_multiplexer) {
+        return Ok(TmuxBaseIndices {
+            base_index: 0,
+            pane_base_index: 0,
+        });
+    }
+
     // `args` will result in the following command:
     // `tmux start-server\; show-option -g base-index\; show-window-option -g pane-base-index`
 
@@ -512,7 +498,7 @@ fn get_tmux_base_indices(
         &config.terminal_multiplexer,
         &commands[0].0,
         commands[0].1,
-    );
+    )?;
     let pane_base_index_re = Regex::new(r"(?:base-index (?P<base_index>\d+))?(?:.*\n)?(?:pane-base-index (?P<pane_base_index>\d+))?").unwrap();
 
     // NOTE: This is a bit redundant but feels _better_ than using Option
@@ -520,46 +506,58 @@ fn get_tmux_base_indices(
     let mut base_index = 0;
     let mut pane_base_index = 0;
 
-    if let Some(captures) =
-        pane_base_index_re.captures(&String::from_utf8(output.unwrap().stdout).unwrap())
-    {
+    if let Some(captures) = pane_base_index_re.captures(&String::from_utf8(output.stdout)?) {
         base_index = captures
             .name("base_index")
             .map_or("0", |m| m.as_str())
-            .parse::<usize>()
-            .unwrap();
+            .parse::<usize>()?;
 
         pane_base_index = captures
             .name("pane_base_index")
             .map_or("0", |m| m.as_str())
-            .parse::<usize>()
-            .unwrap();
+            .parse::<usize>()?;
     }
 
-    let tmux_base_indices = TmuxBaseIndices {
-        base_index: base_index,
-        pane_base_index: pane_base_index,
-    };
+    Ok(TmuxBaseIndices {
+        base_index,
+        pane_base_index,
+    })
+}
 
-    tmux_base_indices
+/// Note which profile (if any) was applied to the project config.
+fn print_profile(config: &Config) {
+    if let Some(profile) = config.profile() {
+        eprintln!("Using profile \"{}\"", profile);
+    }
+}
+
+/// Fail if the project uses settings which its terminal multiplexer can't
+/// support at all and warn about those it will ignore.
+fn check_backend(config: &Config) -> Result<(), Box<dyn Error>> {
+    if is_screen(&config.terminal_multiplexer) {
+        let layouts = ScreenBackend::unsupported_layouts(config);
+        if !layouts.is_empty() {
+            return Err(format!(
+                "screen can't arrange panes, so layouts can't be used (found: {})",
+                layouts.join(", ")
+            )
+            .into());
+        }
+        for warning in ScreenBackend::unsupported_options(config) {
+            eprintln!("Warning: {}", warning);
+        }
+    }
+    Ok(())
 }
 
-fn run_start_(
+/// Start a project's session using the given runner (see TmuxCommandRunner).
+pub fn run_start_with(
     config: Config,
     tmux_command_runner: &dyn TmuxCommandRunner,
 ) -> Result<(), Box<dyn Error>> {
-    let base_indices = get_tmux_base_indices(&config, tmux_command_runner);
-    let commands = convert_config_to_tmux_commands(&config, base_indices);
-    for command in commands {
-        // TODO: run_tmux_command output should be handled and used to report
-        // errors to the user.
-        let _ = tmux_command_runner.run_tmux_command(
-            &config.terminal_multiplexer,
-            &command.0,
-            command.1,
-        );
-    }
-    Ok(())
+    check_backend(&config)?;
+    let plan = config.plan(&PlanEnv::detect_with(&config, tmux_command_runner)?);
+    plan.execute(tmux_command_runner)
 }
 
 pub fn run_start(config: Config) -> Result<(), Box<dyn Error>> {
@@ -570,25 +568,267 @@ pub fn run_start(config: Config) -> Result<(), Box<dyn Error>> {
     // This is the best approach I've hit upon yet but I'm still not convinced
     // it's a good, worthwhile idea.
     // - ethagnawl
-    run_start_(config, &TmuxWrapper)
+    run_start_with(config, &TmuxWrapper)
 }
 
-fn run_debug_(
+/// Print the commands which would be used to start a project's session,
+/// using the given runner to detect the base-index and pane-base-index.
+pub fn run_debug_with(
     config: Config,
     tmux_command_runner: &dyn TmuxCommandRunner,
 ) -> Result<(), Box<dyn Error>> {
-    let base_indices = get_tmux_base_indices(&config, tmux_command_runner);
-    for command in convert_config_to_tmux_commands(&config, base_indices) {
-        println!("{} {}", &config.terminal_multiplexer, command.0.join(" "));
+    print_profile(&config);
+    check_backend(&config)?;
+    let plan = config.plan(&PlanEnv::detect_with(&config, tmux_command_runner)?);
+    for planned_command in plan.iter() {
+        println!(
+            "{} {}",
+            &plan.terminal_multiplexer,
+            plan.args(&planned_command.command).join(" ")
+        );
     }
 
     Ok(())
 }
 
 pub fn run_debug(config: Config) -> Result<(), Box<dyn Error>> {
-    // See run_start docstring for TmuxWrapper rationale.
-    // - ethagnawl
-    run_debug_(config, &TmuxWrapper)
+    run_debug_with(config, &TmuxWrapper)
+}
+
+#[derive(Serialize)]
+struct DebugCommand {
+    argv: Vec<String>,
+    wait: bool,
+    provenance: Provenance,
+}
+
+fn build_debug_json(plan: &Plan) -> Result<String, Box<dyn Error>> {
+    // Unlike the text output, argv includes the terminal multiplexer so that
+    // each entry is a complete, runnable command.
+    let commands: Vec<DebugCommand> = plan
+        .iter()
+        .map(|planned_command| {
+            let mut argv = vec![plan.terminal_multiplexer.clone()];
+            argv.extend(plan.args(&planned_command.command));
+            DebugCommand {
+                argv,
+                wait: planned_command.wait,
+                provenance: planned_command.provenance.clone(),
+            }
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&commands)?)
+}
+
+/// See run_debug_json and run_debug_with.
+pub fn run_debug_json_with(
+    config: Config,
+    tmux_command_runner: &dyn TmuxCommandRunner,
+) -> Result<(), Box<dyn Error>> {
+    print_profile(&config);
+    check_backend(&config)?;
+    let plan = config.plan(&PlanEnv::detect_with(&config, tmux_command_runner)?);
+    println!("{}", build_debug_json(&plan)?);
+
+    Ok(())
+}
+
+/// Print the commands which would be run for a project as a JSON array. Each
+/// entry has the command's argv, whether rmuxinator waits for it to exit and
+/// the part of the project config which caused it.
+pub fn run_debug_json(config: Config) -> Result<(), Box<dyn Error>> {
+    run_debug_json_with(config, &TmuxWrapper)
+}
+
+/// Print the project config as it will be used, i.e. after it's been merged
+/// into any config it extends and its templates have been rendered.
+pub fn run_debug_config(config: Config) -> Result<(), Box<dyn Error>> {
+    print_profile(&config);
+    print!("{}", config.to_toml()?);
+    Ok(())
+}
+
+/// Print any problems found by `Config::validate`, failing if any of them
+/// are errors.
+pub fn run_validate(config: Config) -> Result<(), Box<dyn Error>> {
+    let diagnostics = config.validate();
+    for diagnostic in &diagnostics {
+        eprintln!("{}: {}", diagnostic.severity, diagnostic);
+    }
+
+    let error_count = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .count();
+    match error_count {
+        0 => {
+            println!("{} is valid.", config.name);
+            Ok(())
+        }
+        1 => Err("Found 1 error".into()),
+        _ => Err(format!("Found {} errors", error_count).into()),
+    }
+}
+
+fn build_list_windows_args(session_name: &str) -> Vec<String> {
+    vec![
+        String::from("list-windows"),
+        String::from("-t"),
+        String::from(session_name),
+        String::from("-F"),
+        String::from("#{window_index} #{window_layout} #{window_name}"),
+    ]
+}
+
+fn build_list_panes_args(session_name: &str, window_index: &str) -> Vec<String> {
+    vec![
+        String::from("list-panes"),
+        String::from("-t"),
+        format!("{}:{}", session_name, window_index),
+        String::from("-F"),
+        String::from("#{pane_current_command} #{pane_current_path}"),
+    ]
+}
+
+fn is_shell_command(command: &str) -> bool {
+    // Login shells are reported with a leading dash (e.g. -bash)
+    let command_ = command.trim_start_matches('-');
+    [
+        "ash", "bash", "csh", "dash", "fish", "ksh", "sh", "tcsh", "zsh",
+    ]
+    .contains(&command_)
+}
+
+fn run_freeze_query(
+    terminal_multiplexer: &str,
+    command: &[String],
+    tmux_command_runner: &dyn TmuxCommandRunner,
+) -> Result<String, Box<dyn Error>> {
+    let output = tmux_command_runner.run_tmux_command(terminal_multiplexer, command, false)?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().into());
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+fn freeze_session(
+    session_name: &str,
+    terminal_multiplexer: &str,
+    tmux_command_runner: &dyn TmuxCommandRunner,
+) -> Result<Config, Box<dyn Error>> {
+    let mut windows = vec![];
+
+    let list_windows_output = run_freeze_query(
+        terminal_multiplexer,
+        &build_list_windows_args(session_name),
+        tmux_command_runner,
+    )?;
+
+    for window_line in list_windows_output.lines() {
+        // NOTE: tmux replaces control characters (e.g. tabs) in format
+        // output, so fields are separated by spaces and the only field which
+        // can contain spaces (i.e. the name) comes last.
+        let window_parts: Vec<&str> = window_line.splitn(3, ' ').collect();
+        if window_parts.len() != 3 {
+            return Err(format!("Unexpected list-windows output: {}", window_line).into());
+        }
+        let (window_index, window_layout, window_name) =
+            (window_parts[0], window_parts[1], window_parts[2]);
+
+        let list_panes_output = run_freeze_query(
+            terminal_multiplexer,
+            &build_list_panes_args(session_name, window_index),
+            tmux_command_runner,
+        )?;
+
+        let mut window_start_directory = None;
+        let mut panes = vec![];
+        for pane_line in list_panes_output.lines() {
+            // See above re: field order
+            let pane_parts: Vec<&str> = pane_line.splitn(2, ' ').collect();
+            if pane_parts.len() != 2 {
+                return Err(format!("Unexpected list-panes output: {}", pane_line).into());
+            }
+            let (pane_command, pane_path) = (pane_parts[0], pane_parts[1]);
+
+            // The window's start_directory is inherited from its first pane
+            // and subsequent panes only override it when necessary.
+            if window_start_directory.is_none() {
+                window_start_directory = Some(String::from(pane_path));
+            }
+            let pane_start_directory = if window_start_directory.as_deref() == Some(pane_path) {
+                None
+            } else {
+                Some(String::from(pane_path))
+            };
+
+            let commands = if is_shell_command(pane_command) {
+                vec![]
+            } else {
+                vec![String::from(pane_command)]
+            };
+
+            panes.push(Pane {
+                commands,
+                start_directory: pane_start_directory,
+                ..Pane::default()
+            });
+        }
+
+        windows.push(Window {
+            layout: Some(Layout::Custom(String::from(window_layout))),
+            name: Some(String::from(window_name)),
+            panes,
+            start_directory: window_start_directory,
+            ..Window::default()
+        });
+    }
+
+    Ok(Config {
+        attached: ConfigDefaultValues::default_as_true(),
+        name: String::from(session_name),
+        terminal_multiplexer: String::from(terminal_multiplexer),
+        windows,
+        ..Config::default()
+    })
+}
+
+pub fn run_freeze(
+    session_name: &str,
+    format: Option<ConfigFormat>,
+    output_path: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    let config = freeze_session(
+        session_name,
+        &ConfigDefaultValues::default_as_tmux(),
+        &TmuxWrapper,
+    )?;
+    write_config_output(&config, format, output_path)
+}
+
+fn write_output(contents: &str, output_path: Option<&str>) -> Result<(), Box<dyn Error>> {
+    match output_path {
+        Some(output_path_) => {
+            let mut output_file = File::create(output_path_)?;
+            output_file.write_all(contents.as_bytes())?;
+        }
+        None => print!("{}", contents),
+    }
+
+    Ok(())
+}
+
+/// Write a project config in the given format or, if there isn't one, the
+/// format implied by the output file's extension (falling back to TOML).
+fn write_config_output(
+    config: &Config,
+    format: Option<ConfigFormat>,
+    output_path: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    let format_ = format
+        .or_else(|| output_path.and_then(ConfigFormat::from_path))
+        .unwrap_or(ConfigFormat::Toml);
+    write_output(&config.to_format(format_)?, output_path)
 }
 
 pub fn parse_args<I, T>(args: I) -> CliArgs
@@ -597,8 +837,38 @@ where
     T: Into<OsString> + Clone,
 {
     let project_config_file_arg = Arg::with_name("PROJECT_CONFIG_FILE")
-        .help("The path to the project config file")
+        .help("The path to the project config file (or - to read it from stdin)")
         .required(true);
+    let output_file_arg = Arg::with_name("OUTPUT_FILE")
+        .help("The path to write the project config file to (defaults to stdout)")
+        .short("o")
+        .long("output")
+        .takes_value(true);
+    let config_format_arg = Arg::with_name("CONFIG_FORMAT")
+        .help("The format of the project config file (inferred from its extension by default, falling back to toml, and required when reading it from stdin)")
+        .long("config-format")
+        .takes_value(true)
+        .possible_values(&ConfigFormat::NAMES)
+        // There's no extension to infer it from.
+        .required_if("PROJECT_CONFIG_FILE", "-");
+    // Commands which don't print anything can also take the config format as
+    // --format (debug and export use that for the format of their output).
+    let config_format_or_format_arg = config_format_arg.clone().short("f").visible_alias("format");
+    let variables_arg = Arg::with_name("VARIABLES")
+        .help("Values for the project config's template variables (e.g. service=billing)")
+        .multiple(true)
+        .validator(|variable| {
+            if variable.contains('=') {
+                Ok(())
+            } else {
+                Err(format!("{} must be of the form name=value", variable))
+            }
+        });
+    let profile_arg = Arg::with_name("PROFILE")
+        .help("The profile (i.e. entry in the project config's profiles table) to apply (defaults to $RMUXINATOR_PROFILE, if the project defines it)")
+        .short("p")
+        .long("profile")
+        .takes_value(true);
     let app_matches = App::new(clap::crate_name!())
         .version(clap::crate_version!())
         .author(clap::crate_authors!())
@@ -608,11 +878,87 @@ where
             SubCommand::with_name("debug")
             .about("Print the tmux commands that would be used to start and configure a tmux session using a path to a project config file")
             .arg(&project_config_file_arg)
+            .arg(&variables_arg)
+            .arg(&config_format_arg)
+            .arg(&profile_arg)
+            .arg(
+                Arg::with_name("FORMAT")
+                .help("The format to print the commands in (or config to print the project config, e.g. after merging any config it extends, instead)")
+                .short("f")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["config", "json", "text"])
+                .default_value("text")
+            )
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+            .about("Convert a project config file into a format which can be used without rmuxinator")
+            .arg(&project_config_file_arg)
+            .arg(&variables_arg)
+            .arg(&config_format_arg)
+            .arg(&profile_arg)
+            .arg(
+                Arg::with_name("FORMAT")
+                .help("The format to convert the project config file into")
+                .short("f")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&["json", "sh", "tmux", "toml", "yaml", "zellij"])
+                .required(true)
+            )
+            .arg(&output_file_arg)
+        )
+        .subcommand(
+            SubCommand::with_name("freeze")
+            .about("Capture a running tmux session as a project config file")
+            .arg(
+                Arg::with_name("SESSION_NAME")
+                .help("The name of the tmux session to capture")
+                .required(true)
+            )
+            .arg(
+                Arg::with_name("FORMAT")
+                .help("The format to write the project config file in (inferred from the output file's extension by default, falling back to toml)")
+                .short("f")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&ConfigFormat::NAMES)
+            )
+            .arg(&output_file_arg)
+        )
+        .subcommand(
+            SubCommand::with_name("import")
+            .about("Convert another tool's project file into a project config file")
+            .arg(
+                Arg::with_name("SOURCE_FORMAT")
+                .help("The tool which the project file belongs to")
+                .possible_values(&["tmuxinator", "tmuxp"])
+                .required(true)
+            )
+            .arg(
+                Arg::with_name("SOURCE_FILE")
+                .help("The path to the project file to convert")
+                .required(true)
+            )
+            .arg(&output_file_arg)
         )
         .subcommand(
             SubCommand::with_name("start")
             .about("Start a tmux session using a path to a project config file")
             .arg(&project_config_file_arg)
+            .arg(&variables_arg)
+            .arg(&config_format_or_format_arg)
+            .arg(&profile_arg)
+        )
+        .subcommand(
+            SubCommand::with_name("validate")
+            .about("Check a project config file for problems (e.g. duplicate window names)")
+            .alias("lint")
+            .arg(&project_config_file_arg)
+            .arg(&variables_arg)
+            .arg(&config_format_or_format_arg)
+            .arg(&profile_arg)
         )
         .get_matches_from(args);
 
@@ -632,19 +978,51 @@ where
 
     let project_name = command_matches
         .value_of("PROJECT_CONFIG_FILE")
-        .expect("project file is required by clap")
+        .or_else(|| command_matches.value_of("SESSION_NAME"))
+        .or_else(|| command_matches.value_of("SOURCE_FILE"))
+        .expect("project file or session name is required by clap")
         .to_string();
 
+    let format = command_matches
+        .value_of("SOURCE_FORMAT")
+        .or_else(|| command_matches.value_of("FORMAT"))
+        .map(String::from);
+
+    let config_format = command_matches.value_of("CONFIG_FORMAT").map(String::from);
+
+    let output_path = command_matches.value_of("OUTPUT_FILE").map(String::from);
+
+    let profile = command_matches.value_of("PROFILE").map(String::from);
+
+    let variables = command_matches
+        .values_of("VARIABLES")
+        .map(|values| {
+            values
+                .filter_map(|variable| variable.split_once('='))
+                .map(|(name, value)| (String::from(name), String::from(value)))
+                .collect()
+        })
+        .unwrap_or_default();
+
     CliArgs {
         command,
+        config_format,
+        format,
+        output_path,
+        profile,
         project_name,
+        variables,
     }
 }
 
 #[derive(Debug, PartialEq)]
 pub enum CliCommand {
     Debug,
+    Export,
+    Freeze,
+    Import,
     Start,
+    Validate,
 }
 
 #[derive(Debug)]
@@ -667,7 +1045,11 @@ impl FromStr for CliCommand {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "debug" => Ok(Self::Debug),
+            "export" => Ok(Self::Export),
+            "freeze" => Ok(Self::Freeze),
+            "import" => Ok(Self::Import),
             "start" => Ok(Self::Start),
+            "validate" => Ok(Self::Validate),
             // This should only ever be reached if subcommands are added to
             // clap and not here
             _ => Err(ParseCliCommandError),
@@ -678,13 +1060,26 @@ impl FromStr for CliCommand {
 #[derive(Debug, PartialEq)]
 pub struct CliArgs {
     pub command: CliCommand,
+    // The format of the project config file being read, if it shouldn't be
+    // inferred from the file's extension
+    pub config_format: Option<String>,
+    // The format of the file being read or written, depending on the command
+    // (e.g. import's source format or export's output format)
+    pub format: Option<String>,
+    pub output_path: Option<String>,
+    // The profile to apply to the project config, if one was named on the
+    // command line
+    pub profile: Option<String>,
     // TODO: `project_name` is currently overloaded and also used as the config
-    // path. We should either make this more explicit or introduce separate
-    // args.
+    // path (or, for freeze, the session name and, for import, the source
+    // file). We should either make this more
+    // explicit or introduce separate args.
     pub project_name: String,
+    // Values for template variables, i.e. name=value arguments
+    pub variables: BTreeMap<String, String>,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "kebab-case", untagged)]
 pub enum Layout {
     // NOTE: This does not attempt to do _any_ validation on the provided
@@ -712,27 +1107,161 @@ impl fmt::Display for Layout {
     }
 }
 
-type StartDirectory = Option<String>;
+// NOTE: Layouts are serialized using their names because deriving Serialize
+// for an untagged enum would serialize the presets as unit values.
+impl Serialize for Layout {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl Layout {
+    const PRESET_NAMES: [&'static str; 5] = [
+        "even-horizontal",
+        "even-vertical",
+        "main-horizontal",
+        "main-vertical",
+        "tiled",
+    ];
+
+    /// Whether or not this layout could be a reference to an entry in the
+    /// project's `layouts` table rather than something tmux understands.
+    fn is_reference(&self) -> bool {
+        match self {
+            Layout::Custom(value) => {
+                // e.g. 9959,213x59,0,0[...]
+                static CUSTOM_LAYOUT_RE: OnceLock<Regex> = OnceLock::new();
+                let custom_layout_re = CUSTOM_LAYOUT_RE
+                    .get_or_init(|| Regex::new(r"^[0-9a-f]{4},\d+x\d+,\d+,\d+").unwrap());
+                !Layout::PRESET_NAMES.contains(&value.as_str()) && !custom_layout_re.is_match(value)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn resolve_layout(
+    layout: &Option<Layout>,
+    layouts: &BTreeMap<String, Layout>,
+    referenced_by: &str,
+) -> Result<Option<Layout>, String> {
+    let layout_ = match layout {
+        Some(layout_) => layout_,
+        None => return Ok(None),
+    };
+
+    if let Some(named_layout) = layouts.get(&layout_.to_string()) {
+        return Ok(Some(named_layout.clone()));
+    }
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+    if layout_.is_reference() {
+        let available_layouts = if layouts.is_empty() {
+            String::from("no named layouts are defined")
+        } else {
+            format!(
+                "available layouts: {}",
+                layouts.keys().cloned().collect::<Vec<String>>().join(", ")
+            )
+        };
+        let layout_name = layout_.to_string();
+        let candidates: Vec<&str> = Layout::PRESET_NAMES
+            .iter()
+            .copied()
+            .chain(layouts.keys().map(String::as_str))
+            .collect();
+        let suggestion = match suggest(&layout_name, &candidates) {
+            Some(suggestion_) => format!(". Did you mean \"{}\"?", suggestion_),
+            None => String::new(),
+        };
+        return Err(format!(
+            "Unknown layout \"{}\" referenced by {} ({}){}",
+            layout_name, referenced_by, available_layouts, suggestion
+        ));
+    }
+
+    Ok(Some(layout_.clone()))
+}
+
+pub type StartDirectory = Option<String>;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ResponsiveLayout {
+    pub layout: Layout,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_height: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_width: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_height: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_width: Option<usize>,
+}
+
+impl ResponsiveLayout {
+    fn matches(&self, terminal_size: &Option<TerminalSize>) -> bool {
+        let is_unconstrained = self.max_height.is_none()
+            && self.max_width.is_none()
+            && self.min_height.is_none()
+            && self.min_width.is_none();
+
+        match terminal_size {
+            Some(size) => {
+                self.max_height.is_none_or(|max| size.height <= max)
+                    && self.max_width.is_none_or(|max| size.width <= max)
+                    && self.min_height.is_none_or(|min| size.height >= min)
+                    && self.min_width.is_none_or(|min| size.width >= min)
+            }
+            // We can't evaluate size conditions without a terminal, so only
+            // an unconstrained entry (i.e. a catch-all) can match.
+            None => is_unconstrained,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Pane {
     pub commands: Vec<String>,
+    /// Items to repeat the pane over (see `Config::expand_for_each`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub for_each: Option<ForEach>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub start_directory: StartDirectory,
+    /// Conditions for creating the pane (see `Condition`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub when: Option<Condition>,
 }
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Window {
+    /// Items to repeat the window over (see `Config::expand_for_each`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub for_each: Option<ForEach>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub layout: Option<Layout>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
     #[serde(default)]
     pub panes: Vec<Pane>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub responsive_layouts: Vec<ResponsiveLayout>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub start_directory: StartDirectory,
+    /// Conditions for creating the window (see `Condition`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub when: Option<Condition>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// A tmux hook (see the HOOKS section of tmux's man page).
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "kebab-case")]
-enum HookName {
+pub enum HookName {
     // TODO: Does this make sense? If not, document exclusion.
     // AfterNewSession,
 
@@ -804,36 +1333,95 @@ impl fmt::Display for HookName {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+impl FromStr for HookName {
+    type Err = String;
+
+    /// Parse a hook name as it appears in config files (e.g.
+    /// "client-attached").
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let deserializer: serde::de::value::StrDeserializer<serde::de::value::Error> =
+            s.into_deserializer();
+        HookName::deserialize(deserializer).map_err(|_| format!("Unknown hook name \"{}\"", s))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Hook {
     command: String,
     name: HookName,
 }
 
-#[derive(Derivative, Debug, Default, Deserialize, Serialize)]
+impl Hook {
+    pub fn new(name: HookName, command: &str) -> Hook {
+        Hook {
+            command: String::from(command),
+            name,
+        }
+    }
+
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    pub fn name(&self) -> &HookName {
+        &self.name
+    }
+}
+
+#[derive(Clone, Derivative, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     #[serde(default = "ConfigDefaultValues::default_as_tmux")]
     pub terminal_multiplexer: String,
     // TODO: add base_index w/ default?
     #[serde(default = "ConfigDefaultValues::default_as_true")]
     pub attached: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub pane_name_user_option: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub hooks: Vec<Hook>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub layout: Option<Layout>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub layouts: BTreeMap<String, Layout>,
     pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub start_directory: StartDirectory,
     #[derivative(Default(value = "None"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tmux_options: Option<String>,
+    /// Overlays which can be applied at start time (see
+    /// `Config::apply_profile`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    profiles: BTreeMap<String, serde_json::Value>,
+    /// The name of the profile which has been applied, if any
+    #[serde(skip)]
+    profile: Option<String>,
+    /// Defaults for template variables (see `Config::apply_variables`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    variables: BTreeMap<String, serde_json::Value>,
     #[serde(default)]
     pub windows: Vec<Window>,
+    /// The directory of the file which the config was loaded from, if any
+    /// (see `Config::expand_for_each`)
+    #[serde(skip)]
+    source_dir: Option<PathBuf>,
 }
 
 impl Config {
-    pub fn new_from_file_path(config_path: &String) -> Result<Config, String> {
-        // Need to return String in failure case because toml::from_str may
-        // return a toml::de::Error.
-        let mut config_file = match File::open(&config_path) {
+    /// Load a project config, inferring its format from the file's extension
+    /// and falling back to TOML.
+    pub fn new_from_file_path(config_path: &str) -> Result<Config, String> {
+        Config::new_from_file_path_with_format(config_path, None)
+    }
+
+    pub fn new_from_file_path_with_format(
+        config_path: &str,
+        format: Option<ConfigFormat>,
+    ) -> Result<Config, String> {
+        // Need to return String in failure case because the deserializers
+        // each have their own error types.
+        let mut config_file = match File::open(config_path) {
             Ok(file) => file,
             Err(_) => return Err(String::from("Unable to open config file.")),
         };
@@ -844,12 +1432,51 @@ impl Config {
             Err(_) => return Err(String::from("Unable to read config file.")),
         }
 
-        let decoded = toml::from_str(&contents);
-
-        match decoded {
-            Ok(config) => Ok(config),
-            Err(error) => Err(error.to_string()),
+        let format_ = format
+            .or_else(|| ConfigFormat::from_path(config_path))
+            .unwrap_or(ConfigFormat::Toml);
+        Config::from_str_with_format(&contents, format_, config_path)
+    }
+
+    pub fn to_toml(&self) -> Result<String, String> {
+        // NOTE: toml::to_string requires that all plain values precede any
+        // tables, which would tie the order of Config's fields to the
+        // serializer. Going through toml::Value sidesteps this.
+        toml::Value::try_from(self)
+            .map(|value| value.to_string())
+            .map_err(|error| error.to_string())
+    }
+
+    /// Replace references to entries in the `layouts` table with the layouts
+    /// they name. This is done automatically by `new_from_file_path`.
+    pub fn resolve_named_layouts(&mut self) -> Result<(), String> {
+        self.layout = resolve_layout(&self.layout, &self.layouts, "layout")?;
+
+        for (window_index, window) in self.windows.iter_mut().enumerate() {
+            window.layout = resolve_layout(
+                &window.layout,
+                &self.layouts,
+                &format!("windows[{}].layout", window_index),
+            )?;
+
+            for (responsive_layout_index, responsive_layout) in
+                window.responsive_layouts.iter_mut().enumerate()
+            {
+                let layout = resolve_layout(
+                    &Some(responsive_layout.layout.clone()),
+                    &self.layouts,
+                    &format!(
+                        "windows[{}].responsive_layouts[{}].layout",
+                        window_index, responsive_layout_index
+                    ),
+                )?;
+                if let Some(layout_) = layout {
+                    responsive_layout.layout = layout_;
+                }
+            }
         }
+
+        Ok(())
     }
 }
 
@@ -881,18 +1508,11 @@ fn convert_pascal_case_to_kebab_case(input: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::MockEnv;
     use mockall::mock;
     use mockall::predicate::*;
     use std::collections::HashMap;
 
-    struct MockEnv(HashMap<String, String>);
-
-    impl EnvProvider for MockEnv {
-        fn get_var(&self, key: &str) -> Option<String> {
-            self.0.get(key).cloned()
-        }
-    }
-
     fn create_dummy_output_instance(status: i32, stdout: Vec<u8>, stderr: Vec<u8>) -> Output {
         // NOTE: There's no simple way to create an arbitrary ExitStatus
         // instance, so we actually have to shell out. We could mock ExitStatus
@@ -914,7 +1534,7 @@ mod tests {
     mock! {
         TmuxCommandRunner {}
         impl TmuxCommandRunner for TmuxCommandRunner {
-            fn run_tmux_command(&self, terminal_multiplexer: &String, command: &[String], wait: bool) -> Result<Output, Box<dyn Error>>;
+            fn run_tmux_command(&self, terminal_multiplexer: &str, command: &[String], wait: bool) -> Result<Output, Box<dyn Error>>;
         }
     }
 
@@ -924,19 +1544,19 @@ mod tests {
             String::from("TMUX"),
             String::from("/tmp/tmux-1000/default,12345,0"),
         )]));
-        assert_eq!(true, in_tmux_context(&env));
+        assert!(in_tmux_context(&env));
     }
 
     #[test]
     fn test_in_tmux_context_returns_false_when_env_var_is_empty_string() {
         let env = MockEnv(HashMap::from([(String::from("TMUX"), String::from(""))]));
-        assert_eq!(false, in_tmux_context(&env));
+        assert!(!in_tmux_context(&env));
     }
 
     #[test]
     fn test_in_tmux_context_returns_false_when_env_var_not_set() {
         let env = MockEnv(HashMap::new());
-        assert_eq!(false, in_tmux_context(&env))
+        assert!(!in_tmux_context(&env))
     }
 
     #[test]
@@ -954,12 +1574,26 @@ mod tests {
                 ))
             });
         let config = Config::default();
-        let indices = get_tmux_base_indices(&config, &tmux_command_runner);
+        let indices = get_tmux_base_indices(&config, &tmux_command_runner).unwrap();
         let expected = 0;
         let actual = indices.base_index;
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_it_fails_to_get_base_indices_when_tmux_cant_be_run() {
+        let mut tmux_command_runner = MockTmuxCommandRunner::new();
+        tmux_command_runner
+            .expect_run_tmux_command()
+            .times(1)
+            .returning(|_x, _y, _z| Err("tmux: command not found".into()));
+        let config = Config::default();
+        let actual = get_tmux_base_indices(&config, &tmux_command_runner)
+            .unwrap_err()
+            .to_string();
+        assert_eq!("tmux: command not found", actual);
+    }
+
     #[test]
     fn test_it_returns_default_pane_base_index_when_no_value_found_in_tmux_session() {
         let mut tmux_command_runner = MockTmuxCommandRunner::new();
@@ -975,7 +1609,7 @@ mod tests {
                 ))
             });
         let config = Config::default();
-        let indices = get_tmux_base_indices(&config, &tmux_command_runner);
+        let indices = get_tmux_base_indices(&config, &tmux_command_runner).unwrap();
         let expected = 0;
         let actual = indices.pane_base_index;
         assert_eq!(expected, actual);
@@ -997,7 +1631,7 @@ mod tests {
             });
 
         let config = Config::default();
-        let indices = get_tmux_base_indices(&config, &tmux_command_runner);
+        let indices = get_tmux_base_indices(&config, &tmux_command_runner).unwrap();
         let expected = 0;
         let actual = indices.base_index;
         assert_eq!(expected, actual);
@@ -1018,7 +1652,7 @@ mod tests {
                 ))
             });
         let config = Config::default();
-        let indices = get_tmux_base_indices(&config, &tmux_command_runner);
+        let indices = get_tmux_base_indices(&config, &tmux_command_runner).unwrap();
         let expected = 0;
         let actual = indices.base_index;
         assert_eq!(expected, actual);
@@ -1039,7 +1673,7 @@ mod tests {
                 ))
             });
         let config = Config::default();
-        let indices = get_tmux_base_indices(&config, &tmux_command_runner);
+        let indices = get_tmux_base_indices(&config, &tmux_command_runner).unwrap();
         let expected = 99;
         let actual = indices.base_index;
         assert_eq!(expected, actual);
@@ -1051,8 +1685,8 @@ mod tests {
         tmux_command_runner
             .expect_run_tmux_command()
             .once()
-            .withf(|_terminal_multiplexer: &String, command: &[String], bool| {
-                *bool == false
+            .withf(|_terminal_multiplexer: &str, command: &[String], bool| {
+                !*bool
                     && *command
                         == vec![
                             "start-server".to_string(),
@@ -1074,7 +1708,7 @@ mod tests {
                 ))
             });
         let config = Config::default();
-        let indices = get_tmux_base_indices(&config, &tmux_command_runner);
+        let indices = get_tmux_base_indices(&config, &tmux_command_runner).unwrap();
         let expected = 99;
         let actual = indices.pane_base_index;
         assert_eq!(expected, actual);
@@ -1092,6 +1726,7 @@ mod tests {
                 name: Some(String::from("a window")),
                 panes: Vec::new(),
                 start_directory: None,
+                ..Window::default()
             }],
             ..Config::default()
         };
@@ -1100,7 +1735,7 @@ mod tests {
         tmux_command_runner
             .expect_run_tmux_command()
             .once()
-            .withf(|_terminal_multiplexer: &String, command: &[String], _| {
+            .withf(|_terminal_multiplexer: &str, command: &[String], _| {
                 *command
                     == vec![
                         "-f".to_string(),
@@ -1118,26 +1753,24 @@ mod tests {
             })
             .returning(|_x, _y, _z| Ok(create_dummy_output_instance(0, vec![], vec![])));
 
-        tmux_command_runner
-            .expect_run_tmux_command()
-            .once()
-            .withf(
-                move |_terminal_multiplexer: &String, command: &[String], _| {
-                    *command
-                        == vec![
-                            "-f",
-                            "another-one.conf",
-                            "new-session",
-                            "-d",
-                            "-s",
-                            "foo",
-                            "-n",
-                            "a window",
-                        ]
-                },
-            )
+        tmux_command_runner
+            .expect_run_tmux_command()
+            .once()
+            .withf(move |_terminal_multiplexer: &str, command: &[String], _| {
+                *command
+                    == vec![
+                        "-f",
+                        "another-one.conf",
+                        "new-session",
+                        "-d",
+                        "-s",
+                        "foo",
+                        "-n",
+                        "a window",
+                    ]
+            })
             .returning(|_x, _y, _z| Ok(create_dummy_output_instance(0, vec![], vec![])));
-        let _ = run_start_(config, &tmux_command_runner);
+        let _ = run_start_with(config, &tmux_command_runner);
     }
 
     #[test]
@@ -1152,6 +1785,7 @@ mod tests {
                 name: Some(String::from("a window")),
                 panes: Vec::new(),
                 start_directory: None,
+                ..Window::default()
             }],
             ..Config::default()
         };
@@ -1160,7 +1794,7 @@ mod tests {
         tmux_command_runner
             .expect_run_tmux_command()
             .once()
-            .withf(|_terminal_multiplexer: &String, command: &[String], _| {
+            .withf(|_terminal_multiplexer: &str, command: &[String], _| {
                 *command
                     == vec![
                         "-f".to_string(),
@@ -1183,25 +1817,23 @@ mod tests {
         tmux_command_runner
             .expect_run_tmux_command()
             .once()
-            .withf(
-                move |_terminal_multiplexer: &String, command: &[String], _| {
-                    *command
-                        == vec![
-                            "-f",
-                            "another-one.conf",
-                            "-L",
-                            "custom-socket",
-                            "new-session",
-                            "-d",
-                            "-s",
-                            "foo",
-                            "-n",
-                            "a window",
-                        ]
-                },
-            )
+            .withf(move |_terminal_multiplexer: &str, command: &[String], _| {
+                *command
+                    == vec![
+                        "-f",
+                        "another-one.conf",
+                        "-L",
+                        "custom-socket",
+                        "new-session",
+                        "-d",
+                        "-s",
+                        "foo",
+                        "-n",
+                        "a window",
+                    ]
+            })
             .returning(|_x, _y, _z| Ok(create_dummy_output_instance(0, vec![], vec![])));
-        let _ = run_start_(config, &tmux_command_runner);
+        let _ = run_start_with(config, &tmux_command_runner);
     }
 
     #[test]
@@ -1220,7 +1852,60 @@ mod tests {
             .times(1..)
             .returning(|_, _, _| Ok(create_dummy_output_instance(0, vec![], vec![])));
 
-        let _ = run_start_(config, &tmux_command_runner);
+        let _ = run_start_with(config, &tmux_command_runner);
+    }
+
+    #[test]
+    fn test_it_starts_a_screen_session_without_querying_tmux_options() {
+        let config = Config {
+            attached: false,
+            name: String::from("foo"),
+            terminal_multiplexer: String::from("screen"),
+            windows: vec![Window::default()],
+            ..Config::default()
+        };
+
+        let mut tmux_command_runner = MockTmuxCommandRunner::new();
+
+        tmux_command_runner
+            .expect_run_tmux_command()
+            .with(
+                eq(String::from("screen")),
+                eq(vec![String::from("-dmS"), String::from("foo")]),
+                eq(false),
+            )
+            .times(1)
+            .returning(|_, _, _| Ok(create_dummy_output_instance(0, vec![], vec![])));
+
+        let _ = run_start_with(config, &tmux_command_runner);
+    }
+
+    #[test]
+    fn test_it_refuses_to_start_a_screen_session_with_layouts() {
+        let config = Config {
+            name: String::from("foo"),
+            terminal_multiplexer: String::from("screen"),
+            windows: vec![
+                Window {
+                    layout: Some(Layout::EvenHorizontal),
+                    ..Window::default()
+                },
+                Window {
+                    layout: Some(Layout::Tiled),
+                    ..Window::default()
+                },
+            ],
+            ..Config::default()
+        };
+
+        let mut tmux_command_runner = MockTmuxCommandRunner::new();
+        tmux_command_runner.expect_run_tmux_command().times(0);
+
+        let error = run_start_with(config, &tmux_command_runner).unwrap_err();
+        assert_eq!(
+            "screen can't arrange panes, so layouts can't be used (found: windows[0].layout, windows[1].layout)",
+            error.to_string()
+        );
     }
 
     #[test]
@@ -1243,6 +1928,7 @@ mod tests {
                 name: Some(String::from("a window")),
                 panes: Vec::new(),
                 start_directory: None,
+                ..Window::default()
             }],
             ..Config::default()
         };
@@ -1251,7 +1937,7 @@ mod tests {
         tmux_command_runner
             .expect_run_tmux_command()
             .once()
-            .withf(|_terminal_multiplexer: &String, command: &[String], _| {
+            .withf(|_terminal_multiplexer: &str, command: &[String], _| {
                 *command
                     == vec![
                         "start-server".to_string(),
@@ -1270,13 +1956,11 @@ mod tests {
         tmux_command_runner
             .expect_run_tmux_command()
             .once()
-            .withf(
-                move |_terminal_multiplexer: &String, command: &[String], _| {
-                    *command == vec!["new-session", "-d", "-s", "foo", "-n", "a window"]
-                },
-            )
+            .withf(move |_terminal_multiplexer: &str, command: &[String], _| {
+                *command == vec!["new-session", "-d", "-s", "foo", "-n", "a window"]
+            })
             .returning(|_x, _y, _z| Ok(create_dummy_output_instance(0, vec![], vec![])));
-        let _ = run_start_(config, &tmux_command_runner);
+        let _ = run_start_with(config, &tmux_command_runner);
     }
 
     #[test]
@@ -1289,6 +1973,7 @@ mod tests {
                 name: Some(String::from("a window")),
                 panes: Vec::new(),
                 start_directory: None,
+                ..Window::default()
             }],
             ..Config::default()
         };
@@ -1296,7 +1981,7 @@ mod tests {
         tmux_command_runner
             .expect_run_tmux_command()
             .once()
-            .withf(|_terminal_multiplexer: &String, command: &[String], _| {
+            .withf(|_terminal_multiplexer: &str, command: &[String], _| {
                 *command
                     == vec![
                         "start-server".to_string(),
@@ -1315,12 +2000,11 @@ mod tests {
         tmux_command_runner
             .expect_run_tmux_command()
             .once()
-            .withf(|_terminal_multiplexer: &String, command: &[String], bool| {
-                *bool == false
-                    && *command == vec!["new-session", "-d", "-s", "foo", "-n", "a window"]
+            .withf(|_terminal_multiplexer: &str, command: &[String], bool| {
+                !*bool && *command == vec!["new-session", "-d", "-s", "foo", "-n", "a window"]
             })
             .returning(|_x, _y, _z| Ok(create_dummy_output_instance(0, vec![], vec![])));
-        let _ = run_start_(config, &tmux_command_runner);
+        let _ = run_start_with(config, &tmux_command_runner);
     }
 
     #[test]
@@ -1333,6 +2017,7 @@ mod tests {
                 name: Some(String::from("a window")),
                 panes: Vec::new(),
                 start_directory: None,
+                ..Window::default()
             }],
             ..Config::default()
         };
@@ -1341,8 +2026,8 @@ mod tests {
         tmux_command_runner
             .expect_run_tmux_command()
             .once()
-            .withf(|_terminal_multiplexer: &String, command: &[String], bool| {
-                *bool == false
+            .withf(|_terminal_multiplexer: &str, command: &[String], bool| {
+                !*bool
                     && *command
                         == vec![
                             "start-server".to_string(),
@@ -1361,28 +2046,27 @@ mod tests {
         tmux_command_runner
             .expect_run_tmux_command()
             .once()
-            .withf(|_terminal_multiplexer: &String, command: &[String], bool| {
-                *bool == false
-                    && *command == vec!["new-session", "-d", "-s", "foo", "-n", "a window"]
+            .withf(|_terminal_multiplexer: &str, command: &[String], bool| {
+                !*bool && *command == vec!["new-session", "-d", "-s", "foo", "-n", "a window"]
             })
             .returning(|_x, _y, _z| Ok(create_dummy_output_instance(0, vec![], vec![])));
 
         tmux_command_runner
             .expect_run_tmux_command()
             .once()
-            .withf(|_terminal_multiplexer: &String, command: &[String], bool| {
-                *bool == true && *command == vec!["-u", "attach-session", "-t", "foo"]
+            .withf(|_terminal_multiplexer: &str, command: &[String], bool| {
+                *bool && *command == vec!["-u", "attach-session", "-t", "foo"]
             })
             .returning(|_x, _y, _z| Ok(create_dummy_output_instance(0, vec![], vec![])));
 
-        let _ = run_start_(config, &tmux_command_runner);
+        let _ = run_start_with(config, &tmux_command_runner);
     }
 
     #[test]
     fn it_converts_a_pascal_case_string_to_a_kebab_case_string() {
         let pascal = "KebabCase";
         let expected = "kebab-case";
-        let actual = convert_pascal_case_to_kebab_case(&pascal);
+        let actual = convert_pascal_case_to_kebab_case(pascal);
         assert_eq!(expected, actual);
     }
 
@@ -1390,7 +2074,7 @@ mod tests {
     fn it_no_ops_on_a_non_pascal_case_string() {
         let pascal = "foo";
         let expected = "foo";
-        let actual = convert_pascal_case_to_kebab_case(&pascal);
+        let actual = convert_pascal_case_to_kebab_case(pascal);
         assert_eq!(expected, actual);
     }
 
@@ -1407,7 +2091,7 @@ mod tests {
             String::from("-n"),
             window_name.clone().unwrap(),
         ];
-        let actual = build_session_args(&session_name, window_name, &start_directory);
+        let actual = build_session_args(session_name, window_name, &start_directory).to_argv();
         assert_eq!(expected, actual);
     }
 
@@ -1424,7 +2108,7 @@ mod tests {
             String::from("-n"),
             window_name.clone().unwrap(),
         ];
-        let actual = build_session_args(&session_name, window_name, &start_directory);
+        let actual = build_session_args(&session_name, window_name, &start_directory).to_argv();
         assert_eq!(expected, actual);
     }
 
@@ -1439,7 +2123,7 @@ mod tests {
             String::from("-s"),
             String::from(&session_name),
         ];
-        let actual = build_session_args(&session_name, window_name, &start_directory);
+        let actual = build_session_args(&session_name, window_name, &start_directory).to_argv();
         assert_eq!(expected, actual);
     }
 
@@ -1457,72 +2141,157 @@ mod tests {
             String::from("-n"),
             window_name.clone().unwrap(),
             String::from("-c"),
-            String::from(start_directory_),
+            start_directory_,
         ];
-        let actual = build_session_args(&session_name, window_name, &start_directory);
+        let actual = build_session_args(session_name, window_name, &start_directory).to_argv();
         assert_eq!(expected, actual);
     }
 
     #[test]
-    fn it_builds_window_layout_args_without_a_window_layout_or_a_config_layout() {
-        let session_name = "foo";
-        let window_index = 2;
-        let config_layout = None;
-        let window_layout = None;
-        let actual =
-            build_window_layout_args(&session_name, &window_index, &config_layout, &window_layout);
+    fn it_builds_select_layout_args() {
+        let expected = vec![
+            String::from("select-layout"),
+            String::from("-t"),
+            String::from("foo:2"),
+            String::from("main-vertical"),
+        ];
+        let actual = build_select_layout_args("foo", &2, &Layout::MainVertical).to_argv();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn it_selects_no_window_layout_without_a_window_layout_or_a_config_layout() {
+        let actual = select_window_layout(&None, &None, &[], &None);
         assert!(actual.is_none());
     }
 
     #[test]
-    fn it_builds_window_layout_args_with_a_config_layout_and_no_window_layout() {
-        let session_name = "foo";
-        let window_index = 2;
+    fn it_selects_the_config_layout_without_a_window_layout() {
         let config_layout = Some(Layout::EvenHorizontal);
-        let window_layout = None;
-        let expected = vec![
-            String::from("select-layout"),
-            String::from("-t"),
-            format!("{}:{}", &session_name, &window_index),
-            config_layout.clone().unwrap().to_string(),
-        ];
-        let actual =
-            build_window_layout_args(&session_name, &window_index, &config_layout, &window_layout);
-        assert_eq!(expected, actual.unwrap());
+        let actual = select_window_layout(&config_layout, &None, &[], &None);
+        assert_eq!(
+            Some(Layout::EvenHorizontal.to_string()),
+            actual.map(|(layout, _)| layout.to_string())
+        );
     }
 
     #[test]
-    fn it_builds_window_layout_args_with_a_window_layout_and_no_config_layout() {
-        let session_name = "foo";
-        let window_index = 2;
-        let config_layout = None;
+    fn it_selects_the_window_layout_without_a_config_layout() {
         let window_layout = Some(Layout::Tiled);
-        let expected = vec![
-            String::from("select-layout"),
-            String::from("-t"),
-            format!("{}:{}", &session_name, &window_index),
-            window_layout.clone().unwrap().to_string(),
-        ];
-        let actual =
-            build_window_layout_args(&session_name, &window_index, &config_layout, &window_layout);
-        assert_eq!(expected, actual.unwrap());
+        let actual = select_window_layout(&None, &window_layout, &[], &None);
+        assert_eq!(
+            Some(Layout::Tiled.to_string()),
+            actual.map(|(layout, _)| layout.to_string())
+        );
     }
 
     #[test]
-    fn it_builds_window_layout_args_with_a_window_layout_and_a_config_layout() {
-        let session_name = "foo";
-        let window_index = 2;
+    fn it_selects_the_window_layout_over_the_config_layout() {
         let config_layout = Some(Layout::Tiled);
         let window_layout = Some(Layout::EvenHorizontal);
-        let expected = vec![
-            String::from("select-layout"),
-            String::from("-t"),
-            format!("{}:{}", &session_name, &window_index),
-            window_layout.clone().unwrap().to_string(),
+        let actual = select_window_layout(&config_layout, &window_layout, &[], &None);
+        assert_eq!(
+            Some(Layout::EvenHorizontal.to_string()),
+            actual.map(|(layout, _)| layout.to_string())
+        );
+    }
+
+    #[test]
+    fn it_selects_a_matching_responsive_layout() {
+        let config_layout = Some(Layout::Tiled);
+        let window_layout = Some(Layout::EvenHorizontal);
+        let responsive_layouts = vec![
+            ResponsiveLayout {
+                layout: Layout::MainVertical,
+                max_height: None,
+                max_width: None,
+                min_height: None,
+                min_width: Some(200),
+            },
+            ResponsiveLayout {
+                layout: Layout::EvenVertical,
+                max_height: None,
+                max_width: Some(199),
+                min_height: None,
+                min_width: None,
+            },
         ];
-        let actual =
-            build_window_layout_args(&session_name, &window_index, &config_layout, &window_layout);
-        assert_eq!(expected, actual.unwrap());
+        let terminal_size = Some(TerminalSize {
+            width: 120,
+            height: 40,
+        });
+        let actual = select_window_layout(
+            &config_layout,
+            &window_layout,
+            &responsive_layouts,
+            &terminal_size,
+        );
+        let (layout, layout_source) = actual.unwrap();
+        assert_eq!(Layout::EvenVertical.to_string(), layout.to_string());
+        assert_eq!(LayoutSource::ResponsiveLayout(1), layout_source);
+    }
+
+    #[test]
+    fn it_selects_the_window_layout_when_no_responsive_layout_matches() {
+        let config_layout = Some(Layout::Tiled);
+        let window_layout = Some(Layout::EvenHorizontal);
+        let responsive_layouts = vec![ResponsiveLayout {
+            layout: Layout::MainVertical,
+            max_height: None,
+            max_width: None,
+            min_height: None,
+            min_width: Some(200),
+        }];
+        let terminal_size = Some(TerminalSize {
+            width: 120,
+            height: 40,
+        });
+        let actual = select_window_layout(
+            &config_layout,
+            &window_layout,
+            &responsive_layouts,
+            &terminal_size,
+        );
+        assert_eq!(
+            Some(Layout::EvenHorizontal.to_string()),
+            actual.map(|(layout, _)| layout.to_string())
+        );
+    }
+
+    #[test]
+    fn it_only_matches_unconstrained_responsive_layouts_without_a_terminal_size() {
+        let constrained = ResponsiveLayout {
+            layout: Layout::MainVertical,
+            max_height: None,
+            max_width: None,
+            min_height: None,
+            min_width: Some(200),
+        };
+        let unconstrained = ResponsiveLayout {
+            layout: Layout::Tiled,
+            max_height: None,
+            max_width: None,
+            min_height: None,
+            min_width: None,
+        };
+        assert!(!constrained.matches(&None));
+        assert!(unconstrained.matches(&None));
+    }
+
+    #[test]
+    fn it_parses_stty_size_output() {
+        let expected = Some(TerminalSize {
+            width: 213,
+            height: 59,
+        });
+        let actual = parse_terminal_size("59 213\n");
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn it_doesnt_parse_invalid_stty_size_output() {
+        let actual = parse_terminal_size("stty: /dev/tty: No such device");
+        assert!(actual.is_none());
     }
 
     #[test]
@@ -1539,7 +2308,8 @@ mod tests {
             window_name.clone().unwrap(),
         ];
         let actual =
-            build_create_window_args(&session_name, window_index, &window_name, &start_directory);
+            build_create_window_args(session_name, window_index, &window_name, &start_directory)
+                .to_argv();
         assert_eq!(expected, actual);
     }
 
@@ -1560,7 +2330,8 @@ mod tests {
             String::from("/tmp/neat"),
         ];
         let actual =
-            build_create_window_args(&session_name, window_index, &window_name, &start_directory);
+            build_create_window_args(session_name, window_index, &window_name, &start_directory)
+                .to_argv();
         assert_eq!(expected, actual);
     }
 
@@ -1573,7 +2344,7 @@ mod tests {
             String::from("-t"),
             String::from(session_name),
         ];
-        let actual = build_attach_command_args(&session_name);
+        let actual = build_attach_command_args(session_name).to_argv();
         assert_eq!(expected, actual);
     }
 
@@ -1596,6 +2367,78 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn it_resolves_named_layouts() {
+        let custom_layout = String::from(
+            "9959,213x59,0,0[213x24,0,0,4,213x2,0,25,5,213x31,0,28{166x31,0,28,6,46x31,167,28,7}]",
+        );
+        let mut config = Config {
+            layout: Some(Layout::Custom(String::from("editor"))),
+            layouts: BTreeMap::from([(
+                String::from("editor"),
+                Layout::Custom(custom_layout.clone()),
+            )]),
+            name: String::from("foo"),
+            windows: vec![Window {
+                layout: Some(Layout::Custom(String::from("editor"))),
+                ..Window::default()
+            }],
+            ..Config::default()
+        };
+        config.resolve_named_layouts().unwrap();
+        assert_eq!(custom_layout, config.layout.unwrap().to_string());
+        assert_eq!(
+            custom_layout,
+            config.windows[0].layout.clone().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn it_doesnt_resolve_preset_or_custom_layouts() {
+        let custom_layout = String::from(
+            "9959,213x59,0,0[213x24,0,0,4,213x2,0,25,5,213x31,0,28{166x31,0,28,6,46x31,167,28,7}]",
+        );
+        let mut config = Config {
+            layout: Some(Layout::Custom(String::from("main-vertical"))),
+            name: String::from("foo"),
+            windows: vec![Window {
+                layout: Some(Layout::Custom(custom_layout.clone())),
+                ..Window::default()
+            }],
+            ..Config::default()
+        };
+        config.resolve_named_layouts().unwrap();
+        assert_eq!("main-vertical", config.layout.unwrap().to_string());
+        assert_eq!(
+            custom_layout,
+            config.windows[0].layout.clone().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn it_rejects_unknown_named_layouts() {
+        let mut config = Config {
+            layouts: BTreeMap::from([
+                (String::from("editor"), Layout::Tiled),
+                (String::from("logs"), Layout::EvenVertical),
+            ]),
+            name: String::from("foo"),
+            windows: vec![
+                Window::default(),
+                Window {
+                    layout: Some(Layout::Custom(String::from("edtior"))),
+                    ..Window::default()
+                },
+            ],
+            ..Config::default()
+        };
+        let expected = String::from(
+            "Unknown layout \"edtior\" referenced by windows[1].layout (available layouts: editor, logs). Did you mean \"editor\"?",
+        );
+        let actual = config.resolve_named_layouts().unwrap_err();
+        assert_eq!(expected, actual);
+    }
+
     #[test]
     fn it_uses_no_start_directory_when_none_present_for_session_start_directory() {
         let config = Config {
@@ -1605,11 +2448,12 @@ mod tests {
                 name: Some(String::from("a window")),
                 panes: Vec::new(),
                 start_directory: None,
+                ..Window::default()
             }],
             ..Config::default()
         };
 
-        let actual = build_session_start_directory(&config);
+        let actual = build_session_start_directory(&config, config.windows.first());
         assert!(actual.is_none());
     }
 
@@ -1622,7 +2466,7 @@ mod tests {
             ..Config::default()
         };
         let expected = Some(String::from("/foo/bar"));
-        let actual = build_session_start_directory(&config);
+        let actual = build_session_start_directory(&config, config.windows.first());
         assert_eq!(expected, actual);
     }
 
@@ -1636,12 +2480,13 @@ mod tests {
                 name: Some(String::from("a window")),
                 panes: Vec::new(),
                 start_directory: Some(String::from("/bar/baz")),
+                ..Window::default()
             }],
 
             ..Config::default()
         };
         let expected = Some(String::from("/bar/baz"));
-        let actual = build_session_start_directory(&config);
+        let actual = build_session_start_directory(&config, config.windows.first());
         assert_eq!(expected, actual);
     }
 
@@ -1806,10 +2651,20 @@ mod tests {
             String::from("pane-focus-in"),
             String::from("run \"echo hi\""),
         ];
-        let actual = build_hook_args(&hook);
+        let actual = build_hook_args(&hook).to_argv();
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn it_parses_hook_names() {
+        let hook_name: HookName = "after-split-window".parse().unwrap();
+        assert_eq!("after-split-window", hook_name.to_string());
+        assert_eq!(
+            Err(String::from("Unknown hook name \"foo\"")),
+            "foo".parse::<HookName>().map(|name| name.to_string())
+        );
+    }
+
     #[test]
     fn it_builds_rename_pane_args_when_pane_name_and_pane_name_user_option_present() {
         let session_name = "session-name";
@@ -1826,13 +2681,13 @@ mod tests {
             String::from("pane-name"),
         ];
         let actual = build_rename_pane_args(
-            &session_name,
+            session_name,
             window_index,
             pane_index,
             &pane_name_user_option,
             &pane_name,
         );
-        assert_eq!(expected, actual.unwrap());
+        assert_eq!(expected, actual.unwrap().to_argv());
     }
 
     #[test]
@@ -1843,7 +2698,7 @@ mod tests {
         let pane_name_user_option = Some(String::from("pane_name_user_option"));
         let pane_name = None;
         let actual = build_rename_pane_args(
-            &session_name,
+            session_name,
             window_index,
             pane_index,
             &pane_name_user_option,
@@ -1860,7 +2715,7 @@ mod tests {
         let pane_name_user_option = None;
         let pane_name = Some(String::from("pane-name"));
         let actual = build_rename_pane_args(
-            &session_name,
+            session_name,
             window_index,
             pane_index,
             &pane_name_user_option,
@@ -1889,7 +2744,7 @@ mod tests {
             base_index: 0,
             pane_base_index: 0,
         };
-        let actual = convert_config_to_tmux_commands(&config, base_indices);
+        let actual = convert_config_to_tmux_commands(&config, base_indices, None);
         assert_eq!(expected, actual);
     }
 
@@ -1910,13 +2765,191 @@ mod tests {
     fn it_accepts_correct_cli_args() {
         let expected = CliArgs {
             command: CliCommand::Start,
+            config_format: None,
+            format: None,
+            output_path: None,
+            profile: None,
             project_name: String::from("Foo.toml"),
+            variables: BTreeMap::new(),
         };
         let args = vec!["rmuxinator", "start", "Foo.toml"];
         let actual = parse_args(args);
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn it_accepts_a_debug_format() {
+        let expected = CliArgs {
+            command: CliCommand::Debug,
+            config_format: None,
+            format: Some(String::from("json")),
+            output_path: None,
+            profile: None,
+            project_name: String::from("Foo.toml"),
+            variables: BTreeMap::new(),
+        };
+        let args = vec!["rmuxinator", "debug", "--format", "json", "Foo.toml"];
+        let actual = parse_args(args);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn it_accepts_a_config_format() {
+        let expected = CliArgs {
+            command: CliCommand::Start,
+            config_format: Some(String::from("yaml")),
+            format: None,
+            output_path: None,
+            profile: None,
+            project_name: String::from("Foo"),
+            variables: BTreeMap::new(),
+        };
+        let args = vec!["rmuxinator", "start", "--config-format", "yaml", "Foo"];
+        let actual = parse_args(args);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn it_accepts_format_as_the_config_format_for_start() {
+        let expected = CliArgs {
+            command: CliCommand::Start,
+            config_format: Some(String::from("toml")),
+            format: None,
+            output_path: None,
+            profile: None,
+            project_name: String::from("-"),
+            variables: BTreeMap::new(),
+        };
+        let args = vec!["rmuxinator", "start", "-", "--format", "toml"];
+        let actual = parse_args(args);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn it_accepts_template_variables() {
+        let expected = CliArgs {
+            command: CliCommand::Start,
+            config_format: None,
+            format: None,
+            output_path: None,
+            profile: None,
+            project_name: String::from("Foo.toml"),
+            variables: BTreeMap::from([
+                (String::from("port"), String::from("9000")),
+                (String::from("service"), String::from("a=b")),
+            ]),
+        };
+        let args = vec![
+            "rmuxinator",
+            "start",
+            "Foo.toml",
+            "service=a=b",
+            "port=9000",
+        ];
+        let actual = parse_args(args);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn it_accepts_a_profile() {
+        let expected = CliArgs {
+            command: CliCommand::Debug,
+            config_format: None,
+            format: Some(String::from("text")),
+            output_path: None,
+            profile: Some(String::from("work")),
+            project_name: String::from("Foo.toml"),
+            variables: BTreeMap::new(),
+        };
+        let args = vec!["rmuxinator", "debug", "--profile", "work", "Foo.toml"];
+        let actual = parse_args(args);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn it_accepts_lint_as_an_alias_for_validate() {
+        let expected = CliArgs {
+            command: CliCommand::Validate,
+            config_format: None,
+            format: None,
+            output_path: None,
+            profile: None,
+            project_name: String::from("Foo.toml"),
+            variables: BTreeMap::new(),
+        };
+        let args = vec!["rmuxinator", "lint", "Foo.toml"];
+        let actual = parse_args(args);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn it_builds_debug_json_with_provenance() {
+        let config = Config {
+            attached: false,
+            name: String::from("foo"),
+            terminal_multiplexer: String::from("tmux"),
+            tmux_options: Some(String::from("-L foo")),
+            windows: vec![
+                Window::default(),
+                Window {
+                    layout: Some(Layout::Tiled),
+                    panes: vec![Pane {
+                        commands: vec![String::from("top")],
+                        start_directory: Some(String::from("/tmp")),
+                        ..Pane::default()
+                    }],
+                    ..Window::default()
+                },
+            ],
+            ..Config::default()
+        };
+        let plan = config.plan(&PlanEnv {
+            base_index: 1,
+            pane_base_index: 1,
+            ..PlanEnv::default()
+        });
+        let actual: serde_json::Value =
+            serde_json::from_str(&build_debug_json(&plan).unwrap()).unwrap();
+        let expected = serde_json::json!([
+            {
+                "argv": ["tmux", "-L", "foo", "new-session", "-d", "-s", "foo"],
+                "wait": false,
+                "provenance": {"scope": "session", "field": "name"}
+            },
+            {
+                "argv": ["tmux", "-L", "foo", "new-window", "-t", "foo:2"],
+                "wait": false,
+                "provenance": {"scope": "window", "window": 1, "field": "windows[1]"}
+            },
+            {
+                "argv": ["tmux", "-L", "foo", "send-keys", "-t", "foo:2.1", "cd /tmp", "Enter"],
+                "wait": false,
+                "provenance": {
+                    "scope": "pane",
+                    "window": 1,
+                    "pane": 0,
+                    "field": "windows[1].panes[0].start_directory"
+                }
+            },
+            {
+                "argv": ["tmux", "-L", "foo", "send-keys", "-t", "foo:2.1", "top", "Enter"],
+                "wait": false,
+                "provenance": {
+                    "scope": "pane",
+                    "window": 1,
+                    "pane": 0,
+                    "field": "windows[1].panes[0].commands[0]"
+                }
+            },
+            {
+                "argv": ["tmux", "-L", "foo", "select-layout", "-t", "foo:2", "tiled"],
+                "wait": false,
+                "provenance": {"scope": "window", "window": 1, "field": "windows[1].layout"}
+            }
+        ]);
+        assert_eq!(expected, actual);
+    }
+
     #[test]
     fn test_for_tmux_returns_true_when_tmux_exists() {
         let actual = test_for_tmux("tmux");
@@ -1928,4 +2961,102 @@ mod tests {
         let actual = test_for_tmux("xmux");
         assert!(!actual);
     }
+
+    #[test]
+    fn it_freezes_a_running_session() {
+        let mut tmux_command_runner = MockTmuxCommandRunner::new();
+        tmux_command_runner
+            .expect_run_tmux_command()
+            .once()
+            .withf(|_, command: &[String], _| command[0] == "list-windows")
+            .returning(|_x, _y, _z| {
+                Ok(create_dummy_output_instance(
+                    0,
+                    "0 c195,80x24,0,0[80x12,0,0,0,80x11,0,13,1] the editor\n"
+                        .bytes()
+                        .collect(),
+                    vec![],
+                ))
+            });
+        tmux_command_runner
+            .expect_run_tmux_command()
+            .once()
+            .withf(|_, command: &[String], _| command[0] == "list-panes" && command[2] == "foo:0")
+            .returning(|_x, _y, _z| {
+                Ok(create_dummy_output_instance(
+                    0,
+                    "-bash /tmp\nvim /home/user/a project\n".bytes().collect(),
+                    vec![],
+                ))
+            });
+
+        let config = freeze_session("foo", "tmux", &tmux_command_runner).unwrap();
+        let window = &config.windows[0];
+
+        assert_eq!("foo", config.name);
+        assert!(config.attached);
+        assert_eq!(Some(String::from("the editor")), window.name);
+        assert_eq!(
+            "c195,80x24,0,0[80x12,0,0,0,80x11,0,13,1]",
+            window.layout.clone().unwrap().to_string()
+        );
+        assert_eq!(Some(String::from("/tmp")), window.start_directory);
+        assert!(window.panes[0].commands.is_empty());
+        assert_eq!(None, window.panes[0].start_directory);
+        assert_eq!(vec![String::from("vim")], window.panes[1].commands);
+        assert_eq!(
+            Some(String::from("/home/user/a project")),
+            window.panes[1].start_directory
+        );
+    }
+
+    #[test]
+    fn it_reports_tmux_errors_when_freezing_a_session() {
+        let mut tmux_command_runner = MockTmuxCommandRunner::new();
+        tmux_command_runner
+            .expect_run_tmux_command()
+            .once()
+            .returning(|_x, _y, _z| {
+                Ok(create_dummy_output_instance(
+                    1,
+                    vec![],
+                    "can't find session: foo\n".bytes().collect(),
+                ))
+            });
+
+        let actual = freeze_session("foo", "tmux", &tmux_command_runner).unwrap_err();
+        assert_eq!("can't find session: foo", actual.to_string());
+    }
+
+    #[test]
+    fn it_serializes_a_config_to_toml() {
+        let config = Config {
+            attached: true,
+            name: String::from("foo"),
+            terminal_multiplexer: String::from("tmux"),
+            windows: vec![Window {
+                layout: Some(Layout::Custom(String::from("b25f,80x24,0,0,2"))),
+                name: Some(String::from("one")),
+                panes: vec![Pane {
+                    commands: vec![String::from("vim")],
+                    ..Pane::default()
+                }],
+                ..Window::default()
+            }],
+            ..Config::default()
+        };
+        let expected = r#"attached = true
+name = "foo"
+terminal_multiplexer = "tmux"
+
+[[windows]]
+layout = "b25f,80x24,0,0,2"
+name = "one"
+
+[[windows.panes]]
+commands = ["vim"]
+"#;
+        let actual = config.to_toml().unwrap();
+        assert_eq!(expected, actual);
+    }
 }
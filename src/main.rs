@@ -1,28 +1,101 @@
 extern crate rmuxinator;
 
-use rmuxinator::{parse_args, run_debug, run_start, test_for_tmux, CliCommand, Config};
+use rmuxinator::{
+    parse_args, run_debug, run_debug_config, run_debug_json, run_export, run_freeze, run_import,
+    run_start, run_validate, test_for_tmux, CliArgs, CliCommand, Config, ConfigFormat,
+};
 use std::env;
+use std::io;
+use std::process;
 
-fn main() -> Result<(), String> {
-    let tmux_exists = test_for_tmux("tmux");
+fn parse_format(format: Option<&str>) -> Result<Option<ConfigFormat>, String> {
+    format
+        .map(|format_| format_.parse::<ConfigFormat>())
+        .transpose()
+}
+
+fn load_config(cli_args: &CliArgs) -> Result<Config, String> {
+    let config_format = parse_format(cli_args.config_format.as_deref())?;
+    let config = if cli_args.project_name == "-" {
+        Config::new_from_reader(
+            io::stdin(),
+            config_format.expect("config format is required by clap when reading from stdin"),
+            "<stdin>",
+        )
+    } else {
+        Config::new_from_file_path_with_format(&cli_args.project_name, config_format)
+    };
+    let mut config = config.map_err(|error| format!("Problem parsing config file: {}", error))?;
+    config.select_profile(cli_args.profile.as_deref())?;
+    config.apply_variables(&cli_args.variables)?;
+    Ok(config)
+}
 
-    if !tmux_exists {
-        return Err(String::from(
-            "Unable to find tmux. Is it installed and available on $PATH?",
-        ));
+fn require_terminal_multiplexer(terminal_multiplexer: &str) -> Result<(), String> {
+    if test_for_tmux(terminal_multiplexer) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unable to find {}. Is it installed and available on $PATH?",
+            terminal_multiplexer
+        ))
     }
+}
+
+/// Load a project config which will be run using its own terminal_multiplexer
+/// (e.g. screen), rather than tmux.
+fn load_runnable_config(cli_args: &CliArgs) -> Result<Config, String> {
+    let config = load_config(cli_args)?;
+    require_terminal_multiplexer(&config.terminal_multiplexer)?;
+    Ok(config)
+}
 
+fn run() -> Result<(), String> {
     let cli_args = parse_args(env::args_os());
 
-    let config = Config::new_from_file_path(&cli_args.project_name)
-        .map_err(|error| format!("Problem parsing config file: {}", error))?;
+    // Only freeze talks to tmux directly; start and debug check for the
+    // project's own terminal_multiplexer and the rest never run one.
+    if let CliCommand::Freeze = cli_args.command {
+        require_terminal_multiplexer("tmux")?;
+    }
 
-    match cli_args.command {
-        CliCommand::Start => {
-            run_start(config).map_err(|error| format!("Application error: {}", error))
-        }
+    let result = match cli_args.command {
+        CliCommand::Start => run_start(load_runnable_config(&cli_args)?),
         CliCommand::Debug => {
-            run_debug(config).map_err(|error| format!("Application error: {}", error))
+            let config = load_runnable_config(&cli_args)?;
+            match cli_args.format.as_deref() {
+                Some("config") => run_debug_config(config),
+                Some("json") => run_debug_json(config),
+                _ => run_debug(config),
+            }
         }
+        CliCommand::Export => run_export(
+            load_config(&cli_args)?,
+            &cli_args.format.expect("format is required by clap"),
+            cli_args.output_path.as_deref(),
+        ),
+        CliCommand::Freeze => run_freeze(
+            &cli_args.project_name,
+            parse_format(cli_args.format.as_deref())?,
+            cli_args.output_path.as_deref(),
+        ),
+        CliCommand::Import => run_import(
+            &cli_args.format.expect("source format is required by clap"),
+            &cli_args.project_name,
+            cli_args.output_path.as_deref(),
+        ),
+        CliCommand::Validate => run_validate(load_config(&cli_args)?),
+    };
+
+    result.map_err(|error| format!("Application error: {}", error))
+}
+
+fn main() {
+    // NOTE: Returning the error from main would print its Debug
+    // representation, which escapes the newlines in multi-line errors (e.g.
+    // those which show where a config file failed to parse).
+    if let Err(error) = run() {
+        eprintln!("Error: {}", error);
+        process::exit(1);
     }
 }
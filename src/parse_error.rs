@@ -0,0 +1,292 @@
+use regex::Regex;
+
+// Rendering of errors found while parsing project config files, e.g.
+//
+// Foo.toml:3:1: unknown field `start_dir`, expected one of ...
+//   |
+// 3 | start_dir = "/tmp"
+//   | ^^^^^^^^^
+//   = help: did you mean `start_directory`?
+
+/// The candidate which is most similar to `unknown` (if any are similar
+/// enough to plausibly be what was meant).
+pub fn suggest<'a>(unknown: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|candidate| (candidate, strsim::jaro_winkler(unknown, candidate)))
+        .filter(|(_, similarity)| *similarity > 0.8)
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+        .map(|(candidate, _)| *candidate)
+}
+
+/// The (0-based) line and column of `token` and its length, if it appears in
+/// the file as either a key or a quoted value.
+fn locate_token(contents: &str, token: &str, is_key: bool) -> Option<(usize, usize, usize)> {
+    for (line_index, line) in contents.lines().enumerate() {
+        let column = if is_key {
+            // Keys may be quoted, dotted and/or part of a table header, e.g.
+            // `start_dir = ...` or `[[windows.pane]]`
+            let key_end = line.find(['=', ']']).unwrap_or(line.len());
+            let mut offset = 0;
+            line[..key_end].split('.').find_map(|segment| {
+                let segment_offset = offset;
+                offset += segment.len() + 1;
+                let key = segment.trim_matches(|c: char| c.is_whitespace() || c == '[');
+                if key.trim_matches('"') == token {
+                    segment.find(token).map(|column| segment_offset + column)
+                } else {
+                    None
+                }
+            })
+        } else {
+            line.find(&format!("\"{}\"", token))
+                .or_else(|| line.find(&format!("'{}'", token)))
+                .map(|column| column + 1)
+        };
+
+        if let Some(column_) = column {
+            return Some((line_index, column_, token.len()));
+        }
+    }
+
+    None
+}
+
+fn render(
+    config_path: &str,
+    contents: &str,
+    message: &str,
+    location: Option<(usize, usize, usize)>,
+    help: Option<String>,
+) -> String {
+    let mut lines = vec![];
+
+    match location.and_then(|(line_index, column, length)| {
+        contents
+            .lines()
+            .nth(line_index)
+            .map(|source_line| (line_index, column, length, source_line))
+    }) {
+        Some((line_index, column, length, source_line)) => {
+            let line_number = (line_index + 1).to_string();
+            let gutter = " ".repeat(line_number.len());
+            lines.push(format!(
+                "{}:{}:{}: {}",
+                config_path,
+                line_number,
+                column + 1,
+                message
+            ));
+            lines.push(format!("{} |", gutter));
+            lines.push(format!("{} | {}", line_number, source_line));
+            lines.push(format!(
+                "{} | {}{}",
+                gutter,
+                " ".repeat(column),
+                "^".repeat(length.max(1))
+            ));
+            if let Some(help_) = help {
+                lines.push(format!("{} = help: {}", gutter, help_));
+            }
+        }
+        None => {
+            lines.push(format!("{}: {}", config_path, message));
+            if let Some(help_) = help {
+                lines.push(format!("  = help: {}", help_));
+            }
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// The values quoted in a serde error message, e.g. the unknown field and
+/// expected fields in "unknown field `foo`, expected `bar` or `baz`".
+fn quoted_values(message: &str) -> Vec<&str> {
+    let quoted_re = Regex::new(r"`([^`]*)`").unwrap();
+    quoted_re
+        .captures_iter(message)
+        .filter_map(|captures| captures.get(1))
+        .map(|value| value.as_str())
+        .collect()
+}
+
+/// Describe an error, including where it occurred in the file and, for
+/// unknown keys and values, what may have been meant instead. `line_col` is
+/// the (0-based) location reported by the parser, if any.
+fn format_error(
+    config_path: &str,
+    contents: &str,
+    error_message: &str,
+    line_col: Option<(usize, usize)>,
+) -> String {
+    // The location is reported separately.
+    let location_re = Regex::new(r" at line \d+( column \d+)?").unwrap();
+    let message = location_re.replace(error_message, "");
+    // Exclude the key which the error is "for" (TOML only).
+    let details = message.split(" for key `").next().unwrap_or("");
+
+    let unknown_re = Regex::new(r"unknown (field|variant) `").unwrap();
+    let (location, help) = match unknown_re.find(details) {
+        Some(unknown) => {
+            let is_key = details[unknown.start()..].starts_with("unknown field");
+            let values = quoted_values(&details[unknown.start()..]);
+            match values.split_first() {
+                Some((unknown_value, expected_values)) => {
+                    let location = match line_col {
+                        // Parsers which report a location may point at
+                        // either end of the value.
+                        Some((line_index, column)) => contents
+                            .lines()
+                            .nth(line_index)
+                            .and_then(|line| {
+                                line.get(..(column + unknown_value.len()).min(line.len()))
+                                    .and_then(|prefix| prefix.rfind(unknown_value))
+                            })
+                            .or(Some(column))
+                            .map(|column_| (line_index, column_, unknown_value.len())),
+                        None => locate_token(contents, unknown_value, is_key),
+                    };
+                    (
+                        location,
+                        suggest(unknown_value, expected_values)
+                            .map(|suggestion| format!("did you mean `{}`?", suggestion)),
+                    )
+                }
+                None => (None, None),
+            }
+        }
+        None => (
+            line_col.map(|(line_index, column)| (line_index, column, 1)),
+            None,
+        ),
+    };
+
+    render(config_path, contents, &message, location, help)
+}
+
+pub fn format_toml_error(config_path: &str, contents: &str, error: &toml::de::Error) -> String {
+    format_error(config_path, contents, &error.to_string(), error.line_col())
+}
+
+pub fn format_yaml_error(config_path: &str, contents: &str, error: &serde_yaml::Error) -> String {
+    let line_col = error
+        .location()
+        .map(|location| (location.line() - 1, location.column() - 1));
+    format_error(config_path, contents, &error.to_string(), line_col)
+}
+
+pub fn format_json_error(config_path: &str, contents: &str, error: &serde_json::Error) -> String {
+    // serde_json reports line 0 when the location is unknown (e.g. for I/O
+    // errors).
+    let line_col = match error.line() {
+        0 => None,
+        line => Some((line - 1, error.column().saturating_sub(1))),
+    };
+    format_error(config_path, contents, &error.to_string(), line_col)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+
+    fn parse(contents: &str) -> String {
+        let error = toml::from_str::<Config>(contents).unwrap_err();
+        format_toml_error("Foo.toml", contents, &error)
+    }
+
+    #[test]
+    fn it_suggests_similar_values() {
+        let candidates = ["start_directory", "name", "panes"];
+        assert_eq!(Some("start_directory"), suggest("start_dir", &candidates));
+        assert_eq!(Some("panes"), suggest("pane", &candidates));
+        assert_eq!(None, suggest("bork", &candidates));
+    }
+
+    #[test]
+    fn it_points_at_syntax_errors() {
+        let expected = [
+            "Foo.toml:2:6: expected an equals, found an identifier",
+            "  |",
+            "2 | Toml ain't Yaml",
+            "  |      ^",
+        ]
+        .join("\n");
+        assert_eq!(expected, parse("name = \"foo\"\nToml ain't Yaml\n"));
+    }
+
+    #[test]
+    fn it_suggests_known_fields() {
+        let contents = "name = \"foo\"\n\n[[windows]]\n  name = \"one\"\n  [[windows.pane]]\n";
+        let actual = parse(contents);
+        assert!(actual.starts_with("Foo.toml:5:13: unknown field `pane`, expected one of"));
+        assert!(actual.ends_with(
+            &[
+                "  |",
+                "5 |   [[windows.pane]]",
+                "  |             ^^^^",
+                "  = help: did you mean `panes`?",
+            ]
+            .join("\n")
+        ));
+
+        let contents = "name = \"foo\"\nstart_dir = \"/tmp\"\n";
+        assert!(parse(contents).ends_with(
+            &[
+                "2 | start_dir = \"/tmp\"",
+                "  | ^^^^^^^^^",
+                "  = help: did you mean `start_directory`?",
+            ]
+            .join("\n")
+        ));
+    }
+
+    #[test]
+    fn it_suggests_known_hook_names() {
+        let contents =
+            "name = \"foo\"\n\n[[hooks]]\n  command = \"run-shell 'echo hi'\"\n  name = \"client-atached\"\n";
+        assert!(parse(contents).ends_with(
+            &[
+                "5 |   name = \"client-atached\"",
+                "  |           ^^^^^^^^^^^^^^",
+                "  = help: did you mean `client-attached`?",
+            ]
+            .join("\n")
+        ));
+    }
+
+    #[test]
+    fn it_points_at_unknown_yaml_and_json_keys() {
+        let contents = "name: foo\nwindows:\n  - name: one\n    pane:\n      - commands: []\n";
+        let error = serde_yaml::from_str::<Config>(contents).unwrap_err();
+        let expected = [
+            "Foo.yml:4:5: windows[0]: unknown field `pane`, expected one of `for_each`, `layout`, `name`, `panes`, `responsive_layouts`, `start_directory`, `when`",
+            "  |",
+            "4 |     pane:",
+            "  |     ^^^^",
+            "  = help: did you mean `panes`?",
+        ]
+        .join("\n");
+        assert_eq!(expected, format_yaml_error("Foo.yml", contents, &error));
+
+        let contents = "{\n  \"name\": \"foo\",\n  \"start_dir\": \"/tmp\"\n}\n";
+        let error = serde_json::from_str::<Config>(contents).unwrap_err();
+        assert!(format_json_error("Foo.json", contents, &error).ends_with(
+            &[
+                "3 |   \"start_dir\": \"/tmp\"",
+                "  |    ^^^^^^^^^",
+                "  = help: did you mean `start_directory`?",
+            ]
+            .join("\n")
+        ));
+    }
+
+    #[test]
+    fn it_reports_errors_without_a_location() {
+        assert_eq!(
+            "Foo.toml: missing field `name`",
+            parse("attached = false\n")
+        );
+    }
+}
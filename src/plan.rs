@@ -0,0 +1,738 @@
+use crate::command::build_shell_line;
+use crate::{
+    build_args_with_tmux_options_prefix, build_pane_start_directory, build_session_start_directory,
+    build_window_start_directory, get_terminal_size, get_tmux_base_indices, is_screen,
+    select_window_layout, ConditionEnv, Config, LayoutSource, MultiplexerBackend,
+    MultiplexerCommand, Pane, ScreenBackend, TerminalSize, TmuxBackend, TmuxBaseIndices,
+    TmuxCommandRunner, Window,
+};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::error::Error;
+
+/// The part of a project config which caused a command to be run. Window
+/// and pane numbers are positions in the config (i.e. they're unaffected by
+/// base-index and pane-base-index).
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case", tag = "scope")]
+pub enum Provenance {
+    Session {
+        field: String,
+    },
+    Hook {
+        hook: usize,
+        field: String,
+    },
+    Window {
+        window: usize,
+        field: String,
+    },
+    Pane {
+        window: usize,
+        pane: usize,
+        field: String,
+    },
+}
+
+/// A command along with whether or not rmuxinator waits for it to exit (i.e.
+/// when attaching) and the part of the project config which caused it.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PlannedCommand<C = MultiplexerCommand> {
+    pub command: C,
+    pub wait: bool,
+    pub provenance: Provenance,
+}
+
+fn plan_commands<C>(
+    planned_commands: &mut Vec<PlannedCommand<C>>,
+    commands: Vec<C>,
+    wait: bool,
+    provenance: Provenance,
+) {
+    for command in commands {
+        planned_commands.push(PlannedCommand {
+            command,
+            wait,
+            provenance: provenance.clone(),
+        });
+    }
+}
+
+/// A window whose condition holds, along with those of its panes whose
+/// conditions hold, each paired with its position in the config.
+pub struct SelectedWindow<'a> {
+    pub position: usize,
+    pub window: &'a Window,
+    pub panes: Vec<(usize, &'a Pane)>,
+}
+
+// Windows and panes whose conditions don't hold are skipped before any
+// indices are computed, so they don't leave gaps. Their positions in the
+// config are kept for provenance.
+pub fn select_windows<'a>(
+    config: &'a Config,
+    condition_env: &ConditionEnv,
+) -> Vec<SelectedWindow<'a>> {
+    config
+        .windows
+        .iter()
+        .enumerate()
+        .filter(|(_, window)| condition_env.holds(&window.when))
+        .map(|(position, window)| SelectedWindow {
+            position,
+            window,
+            panes: window
+                .panes
+                .iter()
+                .enumerate()
+                .filter(|(_, pane)| condition_env.holds(&pane.when))
+                .collect(),
+        })
+        .collect()
+}
+
+pub fn build_planned_commands<B: MultiplexerBackend>(
+    backend: &B,
+    config: &Config,
+    base_indices: TmuxBaseIndices,
+    terminal_size: Option<TerminalSize>,
+    condition_env: &ConditionEnv,
+) -> Vec<PlannedCommand<B::Command>> {
+    // TODO: We should consider adding sensible line endings
+    // to clearly delineate command boundaries.
+    let mut commands = vec![];
+
+    let session_name = &config.name;
+
+    let windows = select_windows(config, condition_env);
+
+    let session_start_directory = build_session_start_directory(
+        config,
+        windows
+            .first()
+            .map(|selected_window| selected_window.window),
+    );
+
+    let first_window = if let Some(selected_window) = windows.first() {
+        selected_window.window.name.clone()
+    } else {
+        None
+    };
+
+    plan_commands(
+        &mut commands,
+        backend.create_session(session_name, &first_window, &session_start_directory),
+        false,
+        Provenance::Session {
+            field: String::from("name"),
+        },
+    );
+
+    for (hook_index, hook) in config.hooks.iter().enumerate() {
+        plan_commands(
+            &mut commands,
+            backend.set_hook(hook),
+            false,
+            Provenance::Hook {
+                hook: hook_index,
+                field: format!("hooks[{}]", hook_index),
+            },
+        );
+    }
+
+    for (window_position, selected_window) in windows.into_iter().enumerate() {
+        let window = selected_window.window;
+        let window_iterator_index = selected_window.position;
+        let window_field = format!("windows[{}]", window_iterator_index);
+        // The "first" window is created by create_session because tmux always
+        // creates a window when creating a session.
+        // The alternative would be to create all of the project windows and
+        // then kill the first/default one, but I saw unexpected behavior
+        // (first window's commands not being run) when attempting that -- I
+        // think it's because the indexes get shuffled.
+        // The alternative approach would be more explicit and preferable, so
+        // maybe it's worth revisiting.
+        let window_index = base_indices.base_index + window_position;
+        if window_position > 0 {
+            // TODO: This is heavy handed and this logic is _sort of_ duped
+            // in a few places. Maybe each type should have a method which is
+            // able to compute its own starting directory?
+            let window_start_directory =
+                build_window_start_directory(&config.start_directory, &window.start_directory);
+            plan_commands(
+                &mut commands,
+                backend.add_window(
+                    session_name,
+                    window_index,
+                    &window.name,
+                    &window_start_directory,
+                ),
+                false,
+                Provenance::Window {
+                    window: window_iterator_index,
+                    field: window_field.clone(),
+                },
+            );
+        }
+
+        for (pane_position, (pane_iterator_index, pane)) in
+            selected_window.panes.into_iter().enumerate()
+        {
+            let pane_field = format!("{}.panes[{}]", window_field, pane_iterator_index);
+            let pane_provenance = |field: String| Provenance::Pane {
+                window: window_iterator_index,
+                pane: pane_iterator_index,
+                field,
+            };
+            let pane_index = base_indices.pane_base_index + pane_position;
+            // The "first" pane is created by default by the containing window
+            if pane_position > 0 {
+                plan_commands(
+                    &mut commands,
+                    backend.split_pane(session_name, window_index, pane_index),
+                    false,
+                    pane_provenance(pane_field.clone()),
+                );
+            }
+
+            // Conditionally set start_directory for pane.
+            // Unfortunately, this can't be done cleanly using create_pane
+            // because pane 0 is created implicitly.
+            let pane_start_directory = build_pane_start_directory(
+                &config.start_directory,
+                &window.start_directory,
+                &pane.start_directory,
+            );
+            if let Some(pane_start_directory) = pane_start_directory {
+                let start_directory_field = if pane.start_directory.is_some() {
+                    format!("{}.start_directory", pane_field)
+                } else if window.start_directory.is_some() {
+                    format!("{}.start_directory", window_field)
+                } else {
+                    String::from("start_directory")
+                };
+                let command = format!("cd {}", pane_start_directory);
+                plan_commands(
+                    &mut commands,
+                    backend.send_command(session_name, window_index, pane_index, &command),
+                    false,
+                    pane_provenance(start_directory_field),
+                );
+            }
+
+            for (command_index, command) in pane.commands.iter().enumerate() {
+                plan_commands(
+                    &mut commands,
+                    backend.send_command(session_name, window_index, pane_index, command),
+                    false,
+                    pane_provenance(format!("{}.commands[{}]", pane_field, command_index)),
+                );
+            }
+
+            plan_commands(
+                &mut commands,
+                backend.rename_pane(
+                    session_name,
+                    window_index,
+                    pane_index,
+                    &config.pane_name_user_option,
+                    &pane.name,
+                ),
+                false,
+                pane_provenance(format!("{}.name", pane_field)),
+            );
+        }
+
+        let window_layout = select_window_layout(
+            &config.layout,
+            &window.layout,
+            &window.responsive_layouts,
+            &terminal_size,
+        );
+
+        if let Some((window_layout_, layout_source)) = window_layout {
+            let layout_field = match layout_source {
+                LayoutSource::Config => String::from("layout"),
+                LayoutSource::Window => format!("{}.layout", window_field),
+                LayoutSource::ResponsiveLayout(index) => {
+                    format!("{}.responsive_layouts[{}]", window_field, index)
+                }
+            };
+            plan_commands(
+                &mut commands,
+                backend.set_layout(session_name, window_index, window_layout_),
+                false,
+                Provenance::Window {
+                    window: window_iterator_index,
+                    field: layout_field,
+                },
+            );
+        }
+    }
+
+    // TODO: It's not ideal that logic for constructing this command lives in
+    // multiple places (i.e. here and in build_attach_command_args)
+    // I think ideally this would be a _tell, don't ask_ situation and we
+    // either validate that the output is valid (i.e. via Option) and let the
+    // function figure out whether or how to compute the command. This is
+    // probably also something we should do for all of these helper functions.
+    if config.attached {
+        plan_commands(
+            &mut commands,
+            backend.attach(&config.name),
+            true,
+            Provenance::Session {
+                field: String::from("attached"),
+            },
+        );
+    }
+
+    commands
+}
+
+/// The environment which a plan is built for.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlanEnv {
+    pub base_index: usize,
+    pub pane_base_index: usize,
+    /// Used to select responsive layouts
+    pub terminal_size: Option<TerminalSize>,
+    /// Used to decide which windows and panes are created
+    pub conditions: ConditionEnv,
+}
+
+impl PlanEnv {
+    /// Query tmux (or whichever terminal multiplexer the config uses) for its
+    /// base-index and pane-base-index, the controlling terminal for its size
+    /// and this machine for the conditions windows and panes depend on.
+    pub fn detect_with(
+        config: &Config,
+        tmux_command_runner: &dyn TmuxCommandRunner,
+    ) -> Result<PlanEnv, Box<dyn Error>> {
+        let base_indices = get_tmux_base_indices(config, tmux_command_runner)?;
+        Ok(PlanEnv {
+            base_index: base_indices.base_index,
+            pane_base_index: base_indices.pane_base_index,
+            terminal_size: get_terminal_size(),
+            conditions: ConditionEnv::system(),
+        })
+    }
+}
+
+/// The commands which start and configure a project's session, in the order
+/// they're run.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Plan {
+    pub terminal_multiplexer: String,
+    /// Arguments which precede every command (i.e. the config's tmux_options)
+    pub options: Vec<String>,
+    pub commands: Vec<PlannedCommand>,
+}
+
+impl Plan {
+    /// The arguments to pass to the terminal multiplexer in order to run a
+    /// command.
+    pub fn args(&self, command: &MultiplexerCommand) -> Vec<String> {
+        let mut args = self.options.clone();
+        args.extend(command.to_argv());
+        args
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, PlannedCommand> {
+        self.commands.iter()
+    }
+
+    /// Run each of the commands in order, stopping at the first which can't
+    /// be run or which fails (i.e. exits with a non-zero status).
+    pub fn execute(
+        &self,
+        tmux_command_runner: &dyn TmuxCommandRunner,
+    ) -> Result<(), Box<dyn Error>> {
+        for planned_command in self.iter() {
+            let args = self.args(&planned_command.command);
+            let output = tmux_command_runner.run_tmux_command(
+                &self.terminal_multiplexer,
+                &args,
+                planned_command.wait,
+            )?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let mut message = format!(
+                    "`{}` failed ({})",
+                    build_shell_line(&self.terminal_multiplexer, &args),
+                    output.status
+                );
+                if !stderr.trim().is_empty() {
+                    message.push_str(&format!(": {}", stderr.trim()));
+                }
+                return Err(message.into());
+            }
+        }
+        Ok(())
+    }
+}
+
+impl IntoIterator for Plan {
+    type Item = PlannedCommand;
+    type IntoIter = std::vec::IntoIter<PlannedCommand>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.commands.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Plan {
+    type Item = &'a PlannedCommand;
+    type IntoIter = std::slice::Iter<'a, PlannedCommand>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.commands.iter()
+    }
+}
+
+/// The config whose windows are planned, i.e. with any for_each expanded.
+/// Configs which are built in code (rather than loaded) may not have been
+/// expanded yet.
+pub fn expand_for_planning(config: &Config) -> Cow<'_, Config> {
+    let has_for_each = config.windows.iter().any(|window| {
+        window.for_each.is_some() || window.panes.iter().any(|pane| pane.for_each.is_some())
+    });
+    if has_for_each {
+        let mut config_ = config.clone();
+        config_.expand_for_each();
+        Cow::Owned(config_)
+    } else {
+        Cow::Borrowed(config)
+    }
+}
+
+fn into_multiplexer_commands<C: Into<MultiplexerCommand>>(
+    planned_commands: Vec<PlannedCommand<C>>,
+) -> Vec<PlannedCommand> {
+    planned_commands
+        .into_iter()
+        .map(|planned_command| PlannedCommand {
+            command: planned_command.command.into(),
+            wait: planned_command.wait,
+            provenance: planned_command.provenance,
+        })
+        .collect()
+}
+
+impl Config {
+    /// Build the commands which would be used to start and configure this
+    /// project's session, without running them. Windows and panes whose
+    /// `when` conditions don't hold in env.conditions are left out.
+    pub fn plan(&self, env: &PlanEnv) -> Plan {
+        if let Cow::Owned(config) = expand_for_planning(self) {
+            return config.plan(env);
+        }
+
+        let base_indices = TmuxBaseIndices {
+            base_index: env.base_index,
+            pane_base_index: env.pane_base_index,
+        };
+        let condition_env = &env.conditions;
+
+        if is_screen(&self.terminal_multiplexer) {
+            let backend = ScreenBackend::new(
+                &select_windows(self, condition_env)
+                    .iter()
+                    .map(|selected_window| {
+                        (
+                            selected_window.window.name.clone(),
+                            selected_window.panes.len(),
+                        )
+                    })
+                    .collect::<Vec<(Option<String>, usize)>>(),
+            );
+            // tmux_options are meaningless to screen (see
+            // ScreenBackend::unsupported_options)
+            return Plan {
+                terminal_multiplexer: self.terminal_multiplexer.clone(),
+                options: vec![],
+                commands: into_multiplexer_commands(build_planned_commands(
+                    &backend,
+                    self,
+                    base_indices,
+                    env.terminal_size,
+                    condition_env,
+                )),
+            };
+        }
+
+        let options = self
+            .tmux_options
+            .as_ref()
+            .map(|tmux_options| build_args_with_tmux_options_prefix(tmux_options, vec![]))
+            .unwrap_or_default();
+
+        Plan {
+            terminal_multiplexer: self.terminal_multiplexer.clone(),
+            options,
+            commands: into_multiplexer_commands(build_planned_commands(
+                &TmuxBackend,
+                self,
+                base_indices,
+                env.terminal_size,
+                condition_env,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Condition, ForEach, Pane, RecordedCommand, RecordingRunner, ScriptedResponse,
+        ScriptedRunner, TmuxCommand,
+    };
+    use std::collections::HashMap;
+
+    fn build_config() -> Config {
+        Config {
+            attached: true,
+            name: String::from("foo"),
+            terminal_multiplexer: String::from("tmux"),
+            tmux_options: Some(String::from("-L bar")),
+            windows: vec![Window {
+                panes: vec![Pane {
+                    commands: vec![String::from("top")],
+                    ..Pane::default()
+                }],
+                ..Window::default()
+            }],
+            ..Config::default()
+        }
+    }
+
+    fn tmux_commands(plan: &Plan) -> Vec<&TmuxCommand> {
+        plan.iter()
+            .map(|planned_command| match &planned_command.command {
+                MultiplexerCommand::Tmux(command) => command,
+                command => panic!("expected a tmux command, found {:?}", command),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_plans_commands_without_running_them() {
+        let plan = build_config().plan(&PlanEnv {
+            base_index: 1,
+            pane_base_index: 0,
+            ..PlanEnv::default()
+        });
+        let commands = tmux_commands(&plan);
+        assert_eq!(
+            vec![
+                &TmuxCommand::NewSession {
+                    session_name: String::from("foo"),
+                    window_name: None,
+                    start_directory: None,
+                },
+                &TmuxCommand::SendKeys {
+                    target: String::from("foo:1.0"),
+                    command: String::from("top"),
+                },
+                &TmuxCommand::Attach {
+                    session_name: String::from("foo"),
+                    switch_client: false,
+                },
+            ],
+            commands
+        );
+        assert_eq!(vec!["-L", "bar"], plan.options);
+    }
+
+    #[test]
+    fn it_skips_windows_and_panes_whose_conditions_dont_hold() {
+        let when = |condition: Condition| Some(condition);
+        let config = Config {
+            name: String::from("foo"),
+            windows: vec![
+                Window {
+                    name: Some(String::from("docker")),
+                    when: when(Condition {
+                        hostname: Some(String::from("home-*")),
+                        ..Condition::default()
+                    }),
+                    ..Window::default()
+                },
+                Window {
+                    name: Some(String::from("editor")),
+                    panes: vec![
+                        Pane {
+                            commands: vec![String::from("open .")],
+                            when: when(Condition {
+                                os: Some(String::from("macos")),
+                                ..Condition::default()
+                            }),
+                            ..Pane::default()
+                        },
+                        Pane {
+                            commands: vec![String::from("vim")],
+                            when: when(Condition {
+                                env: Some(String::from("EDITOR=vim")),
+                                ..Condition::default()
+                            }),
+                            ..Pane::default()
+                        },
+                    ],
+                    ..Window::default()
+                },
+            ],
+            ..Config::default()
+        };
+        // Conditions are checked against the plan's env rather than this
+        // machine.
+        let plan = config.plan(&PlanEnv {
+            base_index: 1,
+            pane_base_index: 1,
+            conditions: ConditionEnv {
+                vars: HashMap::from([(String::from("EDITOR"), String::from("vim"))]),
+                hostname: Some(String::from("work-laptop")),
+                os: String::from("linux"),
+            },
+            ..PlanEnv::default()
+        });
+        assert_eq!(
+            vec![
+                PlannedCommand {
+                    command: TmuxCommand::NewSession {
+                        session_name: String::from("foo"),
+                        window_name: Some(String::from("editor")),
+                        start_directory: None,
+                    }
+                    .into(),
+                    wait: false,
+                    provenance: Provenance::Session {
+                        field: String::from("name"),
+                    },
+                },
+                PlannedCommand {
+                    command: TmuxCommand::SendKeys {
+                        target: String::from("foo:1.1"),
+                        command: String::from("vim"),
+                    }
+                    .into(),
+                    wait: false,
+                    provenance: Provenance::Pane {
+                        window: 1,
+                        pane: 1,
+                        field: String::from("windows[1].panes[1].commands[0]"),
+                    },
+                },
+            ],
+            plan.commands
+        );
+    }
+
+    #[test]
+    fn it_expands_for_each_when_planning() {
+        let config = Config {
+            name: String::from("foo"),
+            windows: vec![Window {
+                name: Some(String::from("{{ item }}")),
+                for_each: Some(ForEach::Items(vec![
+                    String::from("api"),
+                    String::from("web"),
+                ])),
+                ..Window::default()
+            }],
+            ..Config::default()
+        };
+        let plan = config.plan(&PlanEnv::default());
+        assert_eq!(
+            vec![
+                &TmuxCommand::NewSession {
+                    session_name: String::from("foo"),
+                    window_name: Some(String::from("api")),
+                    start_directory: None,
+                },
+                &TmuxCommand::NewWindow {
+                    target: String::from("foo:1"),
+                    window_name: Some(String::from("web")),
+                    start_directory: None,
+                },
+            ],
+            tmux_commands(&plan)
+        );
+    }
+
+    #[test]
+    fn it_executes_a_modified_plan() {
+        let mut plan = build_config().plan(&PlanEnv::default());
+        plan.commands
+            .retain(|planned_command| !planned_command.wait);
+        let recorder = RecordingRunner::new();
+        plan.execute(&recorder).unwrap();
+        let expected = vec![
+            RecordedCommand {
+                terminal_multiplexer: String::from("tmux"),
+                args: vec![
+                    String::from("-L"),
+                    String::from("bar"),
+                    String::from("new-session"),
+                    String::from("-d"),
+                    String::from("-s"),
+                    String::from("foo"),
+                ],
+                wait: false,
+            },
+            RecordedCommand {
+                terminal_multiplexer: String::from("tmux"),
+                args: vec![
+                    String::from("-L"),
+                    String::from("bar"),
+                    String::from("send-keys"),
+                    String::from("-t"),
+                    String::from("foo:0.0"),
+                    String::from("top"),
+                    String::from("Enter"),
+                ],
+                wait: false,
+            },
+        ];
+        assert_eq!(expected, recorder.commands());
+    }
+
+    #[test]
+    fn it_stops_executing_at_the_first_failing_command() {
+        let plan = build_config().plan(&PlanEnv::default());
+        let runner = ScriptedRunner::new().respond_to(
+            "new-session",
+            ScriptedResponse::failure(1, "duplicate session: foo\n"),
+        );
+        let error = plan.execute(&runner).unwrap_err();
+        assert_eq!(
+            "`tmux -L bar new-session -d -s foo` failed (exit status: 1): duplicate session: foo",
+            error.to_string()
+        );
+        assert_eq!(1, runner.commands().len());
+    }
+
+    #[test]
+    fn it_serializes_a_plan() {
+        let plan = build_config().plan(&PlanEnv::default());
+        let actual = serde_json::to_value(&plan).unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "type": "send-keys",
+                "target": "foo:0.0",
+                "command": "top"
+            }),
+            actual["commands"][1]["command"]
+        );
+        assert_eq!(
+            serde_json::json!({
+                "scope": "pane",
+                "window": 0,
+                "pane": 0,
+                "field": "windows[0].panes[0].commands[0]"
+            }),
+            actual["commands"][1]["provenance"]
+        );
+        assert_eq!(serde_json::json!(true), actual["commands"][2]["wait"]);
+    }
+}
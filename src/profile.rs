@@ -0,0 +1,339 @@
+use crate::extends::merge_values;
+use crate::parse_error::suggest;
+use crate::{Config, EnvProvider, SystemEnv};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+// Profiles are named overlays (e.g. `[profiles.work]`) which are applied to a
+// project config when it's started with `--profile work` (or with
+// $RMUXINATOR_PROFILE set).
+
+const PROFILE_ENV_VAR: &str = "RMUXINATOR_PROFILE";
+
+const REMOVE_PANES_KEY: &str = "remove_panes";
+const REMOVE_WINDOWS_KEY: &str = "remove_windows";
+
+fn name_of(value: &Value) -> Option<&str> {
+    value.get("name").and_then(Value::as_str)
+}
+
+/// The names listed by e.g. `remove_windows`.
+fn names_to_remove(
+    overlay: &mut Map<String, Value>,
+    key: &str,
+    path: &str,
+) -> Result<Vec<String>, String> {
+    match overlay.remove(key) {
+        None => Ok(vec![]),
+        Some(Value::Array(names)) => names
+            .into_iter()
+            .map(|name| match name {
+                Value::String(name_) => Ok(name_),
+                _ => Err(format!("{}.{} must be a list of names", path, key)),
+            })
+            .collect(),
+        Some(_) => Err(format!("{}.{} must be a list of names", path, key)),
+    }
+}
+
+/// Panes which have the same name as a pane in the overlay are overridden by
+/// it and the rest of the overlay's panes are appended.
+fn overlay_panes(mut panes: Vec<Value>, overlay_panes: Vec<Value>) -> Vec<Value> {
+    for overlay_pane in overlay_panes {
+        let existing_pane = name_of(&overlay_pane).and_then(|name| {
+            panes
+                .iter_mut()
+                .find(|existing| name_of(existing) == Some(name))
+        });
+        match existing_pane {
+            Some(existing) => *existing = merge_values(existing.take(), overlay_pane),
+            None => panes.push(overlay_pane),
+        }
+    }
+    panes
+}
+
+/// Override a window's values (and its panes) with those in the overlay.
+fn overlay_window(window: Value, overlay: Value, path: &str) -> Result<Value, String> {
+    let (mut window_, mut overlay_) = match (window, overlay) {
+        (Value::Object(window_), Value::Object(overlay_)) => (window_, overlay_),
+        (_, overlay_) => return Ok(overlay_),
+    };
+
+    let removed_panes = names_to_remove(&mut overlay_, REMOVE_PANES_KEY, path)?;
+    if let Some(Value::Array(panes)) = window_.get_mut("panes") {
+        panes.retain(|pane| {
+            !name_of(pane).is_some_and(|name| removed_panes.iter().any(|removed| removed == name))
+        });
+    }
+
+    if let Some(Value::Array(overlay_panes_)) = overlay_.remove("panes") {
+        let panes = match window_.remove("panes") {
+            Some(Value::Array(panes)) => panes,
+            _ => vec![],
+        };
+        window_.insert(
+            String::from("panes"),
+            Value::Array(overlay_panes(panes, overlay_panes_)),
+        );
+    }
+
+    window_.extend(overlay_);
+    Ok(Value::Object(window_))
+}
+
+/// Apply a profile to a project config. Windows in the profile override the
+/// window with the same name (or are appended), `remove_windows` and
+/// `remove_panes` list the names of windows and panes to remove and
+/// everything else is merged as it is for `extends` (see `merge_values`).
+fn overlay_config(config: Value, profile: Value, profile_path: &str) -> Result<Value, String> {
+    let mut overlay = match profile {
+        Value::Object(overlay_) => overlay_,
+        _ => return Err(format!("{} must be a table", profile_path)),
+    };
+    let removed_windows = names_to_remove(&mut overlay, REMOVE_WINDOWS_KEY, profile_path)?;
+    let overlay_windows = match overlay.remove("windows") {
+        Some(Value::Array(overlay_windows_)) => overlay_windows_,
+        Some(_) => return Err(format!("{}.windows must be a list", profile_path)),
+        None => vec![],
+    };
+
+    let mut config_ = match merge_values(config, Value::Object(overlay)) {
+        Value::Object(config_) => config_,
+        _ => return Err(String::from("Unable to apply profile")),
+    };
+    let mut windows = match config_.remove("windows") {
+        Some(Value::Array(windows_)) => windows_,
+        _ => vec![],
+    };
+    windows.retain(|window| {
+        !name_of(window).is_some_and(|name| removed_windows.iter().any(|removed| removed == name))
+    });
+    for (overlay_window_index, overlay_window_) in overlay_windows.into_iter().enumerate() {
+        let window_path = format!("{}.windows[{}]", profile_path, overlay_window_index);
+        let existing_window = name_of(&overlay_window_).and_then(|name| {
+            windows
+                .iter_mut()
+                .find(|existing| name_of(existing) == Some(name))
+        });
+        match existing_window {
+            Some(existing) => {
+                *existing = overlay_window(existing.take(), overlay_window_, &window_path)?
+            }
+            // There are no panes to remove from new windows.
+            None => windows.push(overlay_window(
+                Value::Object(Map::new()),
+                overlay_window_,
+                &window_path,
+            )?),
+        }
+    }
+    config_.insert(String::from("windows"), Value::Array(windows));
+
+    Ok(Value::Object(config_))
+}
+
+impl Config {
+    /// The project's profiles (i.e. overlays), by name. They're removed once
+    /// one has been applied.
+    pub fn profiles(&self) -> &BTreeMap<String, Value> {
+        &self.profiles
+    }
+
+    /// The name of the profile which has been applied, if any.
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// Apply the profile (i.e. overlay) named `name`, which must be in the
+    /// project's `profiles` table. The table itself is removed from the
+    /// config, which records the name of the applied profile in `profile`.
+    pub fn apply_profile(&mut self, name: &str) -> Result<(), String> {
+        let profile = match self.profiles.remove(name) {
+            Some(profile_) => profile_,
+            None => {
+                let available_profiles = if self.profiles.is_empty() {
+                    String::from("no profiles are defined")
+                } else {
+                    format!(
+                        "available profiles: {}",
+                        self.profiles
+                            .keys()
+                            .cloned()
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    )
+                };
+                let candidates: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+                let suggestion = match suggest(name, &candidates) {
+                    Some(suggestion_) => format!(". Did you mean \"{}\"?", suggestion_),
+                    None => String::new(),
+                };
+                return Err(format!(
+                    "Unknown profile \"{}\" ({}){}",
+                    name, available_profiles, suggestion
+                ));
+            }
+        };
+        self.profiles.clear();
+
+        let config = serde_json::to_value(&*self).map_err(|error| error.to_string())?;
+        let profile_path = format!("profiles.{}", name);
+        let overlaid = overlay_config(config, profile, &profile_path)?;
+
+        let mut config_: Config = serde_json::from_value(overlaid)
+            .map_err(|error| format!("{}: {}", profile_path, error))?;
+        config_.resolve_named_layouts()?;
+        config_.source_dir = self.source_dir.take();
+        config_.expand_for_each();
+        config_.profile = Some(String::from(name));
+        *self = config_;
+        Ok(())
+    }
+
+    /// Apply the profile named on the command line (`profile`) or, failing
+    /// that, by $RMUXINATOR_PROFILE. Unlike the former, the latter is ignored
+    /// if the project doesn't define a profile with that name, so that it can
+    /// be set for all projects (e.g. on a work machine).
+    pub fn select_profile(&mut self, profile: Option<&str>) -> Result<(), String> {
+        self.select_profile_with_env(profile, &SystemEnv)
+    }
+
+    fn select_profile_with_env(
+        &mut self,
+        profile: Option<&str>,
+        env: &dyn EnvProvider,
+    ) -> Result<(), String> {
+        match profile {
+            Some(profile_) => self.apply_profile(profile_),
+            None => match env.get_var(PROFILE_ENV_VAR) {
+                Some(profile_) if self.profiles.contains_key(&profile_) => {
+                    self.apply_profile(&profile_)
+                }
+                _ => Ok(()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::ConfigFormat;
+    use crate::test_support::MockEnv;
+    use std::collections::{BTreeMap, HashMap};
+
+    fn build_config() -> Config {
+        let contents = r#"
+name = "foo"
+tmux_options = "-L home"
+
+[[windows]]
+  name = "editor"
+  [[windows.panes]]
+    name = "vim"
+    commands = ["vim"]
+  [[windows.panes]]
+    name = "shell"
+    commands = []
+[[windows]]
+  name = "vpn"
+  [[windows.panes]]
+    commands = ["sudo openvpn home.ovpn"]
+
+[profiles.work]
+  tmux_options = "-L work"
+  remove_windows = ["vpn"]
+  [[profiles.work.windows]]
+    name = "editor"
+    remove_panes = ["shell"]
+    [[profiles.work.windows.panes]]
+      name = "vim"
+      start_directory = "~/work"
+    [[profiles.work.windows.panes]]
+      commands = ["htop"]
+  [[profiles.work.windows]]
+    name = "chat"
+    [[profiles.work.windows.panes]]
+      commands = ["weechat"]
+[profiles.home]
+"#;
+        Config::from_str_with_format(contents, ConfigFormat::Toml, "Foo.toml").unwrap()
+    }
+
+    #[test]
+    fn it_applies_profiles() {
+        let mut config = build_config();
+        config.apply_profile("work").unwrap();
+
+        assert_eq!(Some("work"), config.profile());
+        assert!(config.profiles().is_empty());
+        assert_eq!(Some(String::from("-L work")), config.tmux_options);
+        let window_names: Vec<Option<String>> = config
+            .windows
+            .iter()
+            .map(|window| window.name.clone())
+            .collect();
+        assert_eq!(
+            vec![Some(String::from("editor")), Some(String::from("chat"))],
+            window_names
+        );
+        let editor_panes = &config.windows[0].panes;
+        assert_eq!(2, editor_panes.len());
+        assert_eq!(vec!["vim"], editor_panes[0].commands);
+        assert_eq!(
+            Some(String::from("~/work")),
+            editor_panes[0].start_directory
+        );
+        assert_eq!(vec!["htop"], editor_panes[1].commands);
+    }
+
+    #[test]
+    fn it_rejects_unknown_profiles() {
+        let mut config = build_config();
+        assert_eq!(
+            Err(String::from(
+                "Unknown profile \"wrk\" (available profiles: home, work). Did you mean \"work\"?"
+            )),
+            config.apply_profile("wrk")
+        );
+    }
+
+    #[test]
+    fn it_rejects_profiles_which_arent_tables() {
+        let mut config = Config {
+            profiles: BTreeMap::from([(String::from("work"), Value::from("-L work"))]),
+            ..build_config()
+        };
+        assert_eq!(
+            Err(String::from("profiles.work must be a table")),
+            config.apply_profile("work")
+        );
+    }
+
+    #[test]
+    fn it_selects_profiles_from_the_environment() {
+        let env = MockEnv(HashMap::from([(
+            String::from(PROFILE_ENV_VAR),
+            String::from("work"),
+        )]));
+        let mut config = build_config();
+        config.select_profile_with_env(Some("home"), &env).unwrap();
+        assert_eq!(Some("home"), config.profile());
+        assert_eq!(Some(String::from("-L home")), config.tmux_options);
+
+        let mut config = build_config();
+        config.select_profile_with_env(None, &env).unwrap();
+        assert_eq!(Some("work"), config.profile());
+
+        // Profiles named by the environment are only applied if they exist.
+        let env = MockEnv(HashMap::from([(
+            String::from(PROFILE_ENV_VAR),
+            String::from("cafe"),
+        )]));
+        let mut config = build_config();
+        config.select_profile_with_env(None, &env).unwrap();
+        assert_eq!(None, config.profile());
+        assert_eq!(2, config.profiles().len());
+    }
+}
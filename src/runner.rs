@@ -0,0 +1,214 @@
+use crate::TmuxCommandRunner;
+use std::cell::RefCell;
+use std::error::Error;
+#[cfg(unix)]
+use std::os::unix::process::ExitStatusExt;
+#[cfg(windows)]
+use std::os::windows::process::ExitStatusExt;
+use std::process::{ExitStatus, Output};
+
+// Runners which don't start any processes, for use in tests.
+
+#[cfg(unix)]
+fn build_exit_status(status: i32) -> ExitStatus {
+    // Raw statuses are as returned by waitpid, i.e. the exit code is in the
+    // second byte.
+    ExitStatus::from_raw(status << 8)
+}
+
+#[cfg(windows)]
+fn build_exit_status(status: i32) -> ExitStatus {
+    ExitStatus::from_raw(status as u32)
+}
+
+fn build_output(status: i32, stdout: &str, stderr: &str) -> Output {
+    Output {
+        status: build_exit_status(status),
+        stdout: stdout.as_bytes().to_vec(),
+        stderr: stderr.as_bytes().to_vec(),
+    }
+}
+
+/// A command which was passed to a runner.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedCommand {
+    pub terminal_multiplexer: String,
+    pub args: Vec<String>,
+    pub wait: bool,
+}
+
+/// Records every command it's asked to run and reports that each of them
+/// succeeded without printing anything.
+///
+/// NOTE: Because nothing is printed, base-index and pane-base-index are
+/// assumed to be 0.
+#[derive(Debug, Default)]
+pub struct RecordingRunner {
+    commands: RefCell<Vec<RecordedCommand>>,
+}
+
+impl RecordingRunner {
+    pub fn new() -> RecordingRunner {
+        RecordingRunner::default()
+    }
+
+    /// The commands which have been run so far, in order.
+    pub fn commands(&self) -> Vec<RecordedCommand> {
+        self.commands.borrow().clone()
+    }
+}
+
+impl TmuxCommandRunner for RecordingRunner {
+    fn run_tmux_command(
+        &self,
+        terminal_multiplexer: &str,
+        command: &[String],
+        wait: bool,
+    ) -> Result<Output, Box<dyn Error>> {
+        self.commands.borrow_mut().push(RecordedCommand {
+            terminal_multiplexer: String::from(terminal_multiplexer),
+            args: command.to_vec(),
+            wait,
+        });
+        Ok(build_output(0, "", ""))
+    }
+}
+
+/// The output a ScriptedRunner produces for a command.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScriptedResponse {
+    pub status: i32,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl ScriptedResponse {
+    pub fn success(stdout: &str) -> ScriptedResponse {
+        ScriptedResponse {
+            status: 0,
+            stdout: String::from(stdout),
+            stderr: String::new(),
+        }
+    }
+
+    pub fn failure(status: i32, stderr: &str) -> ScriptedResponse {
+        ScriptedResponse {
+            status,
+            stdout: String::new(),
+            stderr: String::from(stderr),
+        }
+    }
+}
+
+/// Responds to commands using canned output, e.g. to fake a tmux server
+/// which uses a non-default base-index or one which rejects a command.
+///
+/// A response is used for any command which includes its (sub)command
+/// argument (e.g. "show-option") and the first matching response wins.
+/// Commands without a matching response succeed without printing anything.
+/// Every command is recorded, as in RecordingRunner.
+#[derive(Debug, Default)]
+pub struct ScriptedRunner {
+    recorder: RecordingRunner,
+    responses: Vec<(String, ScriptedResponse)>,
+}
+
+impl ScriptedRunner {
+    pub fn new() -> ScriptedRunner {
+        ScriptedRunner::default()
+    }
+
+    pub fn respond_to(mut self, command: &str, response: ScriptedResponse) -> ScriptedRunner {
+        self.responses.push((String::from(command), response));
+        self
+    }
+
+    /// The commands which have been run so far, in order.
+    pub fn commands(&self) -> Vec<RecordedCommand> {
+        self.recorder.commands()
+    }
+}
+
+impl TmuxCommandRunner for ScriptedRunner {
+    fn run_tmux_command(
+        &self,
+        terminal_multiplexer: &str,
+        command: &[String],
+        wait: bool,
+    ) -> Result<Output, Box<dyn Error>> {
+        self.recorder
+            .run_tmux_command(terminal_multiplexer, command, wait)?;
+        match self
+            .responses
+            .iter()
+            .find(|(command_name, _)| command.contains(command_name))
+        {
+            Some((_, response)) => Ok(build_output(
+                response.status,
+                &response.stdout,
+                &response.stderr,
+            )),
+            None => Ok(build_output(0, "", "")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{run_start_with, Config, PlanEnv, Window};
+
+    fn build_config() -> Config {
+        Config {
+            attached: false,
+            name: String::from("foo"),
+            terminal_multiplexer: String::from("tmux"),
+            windows: vec![Window::default(), Window::default()],
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn it_records_commands() {
+        let runner = RecordingRunner::new();
+        run_start_with(build_config(), &runner).unwrap();
+        let args: Vec<String> = runner
+            .commands()
+            .iter()
+            .map(|command| command.args.join(" "))
+            .collect();
+        assert_eq!(
+            vec![
+                "start-server ; show-option -g base-index ; show-window-option -g pane-base-index",
+                "new-session -d -s foo",
+                "new-window -t foo:1",
+            ],
+            args
+        );
+    }
+
+    #[test]
+    fn it_responds_to_scripted_commands() {
+        let runner = ScriptedRunner::new().respond_to(
+            "show-option",
+            ScriptedResponse::success("base-index 1\npane-base-index 1\n"),
+        );
+        let env = PlanEnv::detect_with(&build_config(), &runner).unwrap();
+        assert_eq!(1, env.base_index);
+        assert_eq!(1, env.pane_base_index);
+        assert_eq!(1, runner.commands().len());
+    }
+
+    #[test]
+    fn it_fails_scripted_commands() {
+        let runner = ScriptedRunner::new().respond_to(
+            "new-session",
+            ScriptedResponse::failure(1, "duplicate session: foo"),
+        );
+        let output = runner
+            .run_tmux_command("tmux", &[String::from("new-session")], false)
+            .unwrap();
+        assert_eq!(Some(1), output.status.code());
+        assert_eq!(b"duplicate session: foo".to_vec(), output.stderr);
+    }
+}
@@ -0,0 +1,342 @@
+use crate::{Config, EnvProvider, SystemEnv};
+use regex::{Captures, Regex};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+// Templates look like `{{ service }}` or `{{ port | default(8080) }}`.
+// Anything else between braces (e.g. `docker ps --format '{{.Names}}'`) is
+// left as is.
+// Environment variables only fill in template variables when they're
+// prefixed (e.g. $RMUXINATOR_VAR_port), so that common variables like $USER
+// or $HOST don't override a project's own values.
+const ENV_VAR_PREFIX: &str = "RMUXINATOR_VAR_";
+
+const TEMPLATE_PATTERN: &str = r#"\{\{\s*([A-Za-z_][A-Za-z0-9_-]*)\s*(?:\|\s*default\(\s*("[^"]*"|'[^']*'|[^)]*?)\s*\)\s*)?\}\}"#;
+
+/// The values of the project's `variables` table as strings.
+fn table_variables(config: &Config) -> Result<BTreeMap<String, String>, String> {
+    config
+        .variables
+        .iter()
+        .map(|(name, value)| {
+            let value_ = match value {
+                Value::String(string) => string.clone(),
+                Value::Number(number) => number.to_string(),
+                Value::Bool(boolean) => boolean.to_string(),
+                Value::Null | Value::Array(_) | Value::Object(_) => {
+                    return Err(format!(
+                        "variables.{} must be a string, number or boolean",
+                        name
+                    ))
+                }
+            };
+            Ok((name.clone(), value_))
+        })
+        .collect()
+}
+
+/// The string values which may contain templates, along with their paths.
+///
+/// NOTE: Layouts are excluded because references to named layouts have
+/// already been resolved by the time templates are rendered.
+fn template_fields(config: &mut Config) -> Vec<(String, &mut String)> {
+    let mut fields = vec![(
+        String::from("terminal_multiplexer"),
+        &mut config.terminal_multiplexer,
+    )];
+
+    if let Some(pane_name_user_option) = config.pane_name_user_option.as_mut() {
+        fields.push((String::from("pane_name_user_option"), pane_name_user_option));
+    }
+    for (hook_index, hook) in config.hooks.iter_mut().enumerate() {
+        fields.push((format!("hooks[{}].command", hook_index), &mut hook.command));
+    }
+    fields.push((String::from("name"), &mut config.name));
+    if let Some(start_directory) = config.start_directory.as_mut() {
+        fields.push((String::from("start_directory"), start_directory));
+    }
+    if let Some(tmux_options) = config.tmux_options.as_mut() {
+        fields.push((String::from("tmux_options"), tmux_options));
+    }
+
+    for (window_index, window) in config.windows.iter_mut().enumerate() {
+        let window_path = format!("windows[{}]", window_index);
+        if let Some(name) = window.name.as_mut() {
+            fields.push((format!("{}.name", window_path), name));
+        }
+        if let Some(start_directory) = window.start_directory.as_mut() {
+            fields.push((format!("{}.start_directory", window_path), start_directory));
+        }
+
+        for (pane_index, pane) in window.panes.iter_mut().enumerate() {
+            let pane_path = format!("{}.panes[{}]", window_path, pane_index);
+            for (command_index, command) in pane.commands.iter_mut().enumerate() {
+                fields.push((
+                    format!("{}.commands[{}]", pane_path, command_index),
+                    command,
+                ));
+            }
+            if let Some(name) = pane.name.as_mut() {
+                fields.push((format!("{}.name", pane_path), name));
+            }
+            if let Some(start_directory) = pane.start_directory.as_mut() {
+                fields.push((format!("{}.start_directory", pane_path), start_directory));
+            }
+        }
+    }
+
+    fields
+}
+
+fn unquote(value: &str) -> &str {
+    let is_quoted = value.len() >= 2
+        && ((value.starts_with('"') && value.ends_with('"'))
+            || (value.starts_with('\'') && value.ends_with('\'')));
+    if is_quoted {
+        &value[1..value.len() - 1]
+    } else {
+        value
+    }
+}
+
+fn template_re() -> &'static Regex {
+    static TEMPLATE_RE: OnceLock<Regex> = OnceLock::new();
+    TEMPLATE_RE.get_or_init(|| Regex::new(TEMPLATE_PATTERN).unwrap())
+}
+
+/// Render the templates in `value`, recording the names of any variables
+/// which couldn't be resolved (and leaving their templates as is).
+pub fn render_templates(
+    value: &str,
+    lookup: &dyn Fn(&str) -> Option<String>,
+    unresolved: &mut Vec<String>,
+) -> String {
+    template_re()
+        .replace_all(value, |captures: &Captures| {
+            let name = &captures[1];
+            match lookup(name).or_else(|| {
+                captures
+                    .get(2)
+                    .map(|default| String::from(unquote(default.as_str())))
+            }) {
+                Some(value_) => value_,
+                None => {
+                    unresolved.push(String::from(name));
+                    String::from(&captures[0])
+                }
+            }
+        })
+        .into_owned()
+}
+
+/// Render only the templates whose variables `lookup` resolves. The rest are
+/// left as is, defaults and all, so that they can be rendered later.
+pub fn render_known_templates(value: &str, lookup: &dyn Fn(&str) -> Option<String>) -> String {
+    template_re()
+        .replace_all(value, |captures: &Captures| {
+            lookup(&captures[1]).unwrap_or_else(|| String::from(&captures[0]))
+        })
+        .into_owned()
+}
+
+impl Config {
+    /// The defaults for template variables from the project's `variables`
+    /// table.
+    pub fn variables(&self) -> &BTreeMap<String, Value> {
+        &self.variables
+    }
+
+    /// Render templates (e.g. `{{ service }}` or `{{ port | default(8080) }}`)
+    /// in the project's values. Variables are looked up in `variables` (e.g.
+    /// those passed on the command line), then the environment (prefixed with
+    /// `RMUXINATOR_VAR_`, e.g. `$RMUXINATOR_VAR_port`) and then the project's
+    /// `variables` table, before falling back to the template's default.
+    pub fn apply_variables(&mut self, variables: &BTreeMap<String, String>) -> Result<(), String> {
+        self.apply_variables_with_env(variables, &SystemEnv)
+    }
+
+    fn apply_variables_with_env(
+        &mut self,
+        variables: &BTreeMap<String, String>,
+        env: &dyn EnvProvider,
+    ) -> Result<(), String> {
+        let table_variables = table_variables(self)?;
+        let lookup = |name: &str| {
+            variables
+                .get(name)
+                .cloned()
+                .or_else(|| env.get_var(&format!("{}{}", ENV_VAR_PREFIX, name)))
+                .or_else(|| table_variables.get(name).cloned())
+        };
+
+        // Variable name -> paths of the values which use it
+        let mut unresolved_variables: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (path, value) in template_fields(self) {
+            let mut unresolved = vec![];
+            *value = render_templates(value, &lookup, &mut unresolved);
+            for name in unresolved {
+                let paths = unresolved_variables.entry(name).or_default();
+                if !paths.contains(&path) {
+                    paths.push(path.clone());
+                }
+            }
+        }
+
+        if unresolved_variables.is_empty() {
+            return Ok(());
+        }
+
+        let mut lines = vec![String::from("Unresolved template variables:")];
+        for (name, paths) in unresolved_variables {
+            lines.push(format!("- {} (used by {})", name, paths.join(", ")));
+        }
+        lines.push(String::from(
+            "Pass them as arguments (e.g. name=value), set them as environment variables (e.g. RMUXINATOR_VAR_name=value) or give them defaults in the variables table.",
+        ));
+        Err(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::MockEnv;
+    use crate::{Pane, Window};
+    use std::collections::HashMap;
+
+    fn build_config() -> Config {
+        Config {
+            name: String::from("{{ service }}"),
+            start_directory: Some(String::from("~/src/{{ service }}")),
+            terminal_multiplexer: String::from("tmux"),
+            windows: vec![Window {
+                panes: vec![Pane {
+                    commands: vec![
+                        String::from("make run PORT={{ port | default(8080) }}"),
+                        String::from("docker ps --format '{{.Names}}'"),
+                    ],
+                    ..Pane::default()
+                }],
+                ..Window::default()
+            }],
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn it_renders_templates() {
+        let lookup = |name: &str| match name {
+            "service" => Some(String::from("billing")),
+            _ => None,
+        };
+        let mut unresolved = vec![];
+        assert_eq!(
+            "billing:8080:a b:{{ .Names }}:{{ foo }}",
+            render_templates(
+                "{{service}}:{{ port | default(8080) }}:{{ x | default(\"a b\") }}:{{ .Names }}:{{ foo }}",
+                &lookup,
+                &mut unresolved
+            )
+        );
+        assert_eq!(vec!["foo"], unresolved);
+    }
+
+    #[test]
+    fn it_renders_known_templates() {
+        let lookup = |name: &str| match name {
+            "item" => Some(String::from("api")),
+            _ => None,
+        };
+        assert_eq!(
+            "api:api:{{ port | default(8080) }}:{{ .Names }}",
+            render_known_templates(
+                "{{ item }}:{{ item | default(web) }}:{{ port | default(8080) }}:{{ .Names }}",
+                &lookup
+            )
+        );
+    }
+
+    #[test]
+    fn it_applies_variables_in_order_of_precedence() {
+        let mut config = Config {
+            variables: BTreeMap::from([
+                (String::from("port"), Value::from(9000)),
+                (String::from("service"), Value::from("table")),
+            ]),
+            ..build_config()
+        };
+        let env = MockEnv(HashMap::from([(
+            String::from("RMUXINATOR_VAR_service"),
+            String::from("env"),
+        )]));
+        config
+            .apply_variables_with_env(
+                &BTreeMap::from([(String::from("service"), String::from("billing"))]),
+                &env,
+            )
+            .unwrap();
+        assert_eq!("billing", config.name);
+        assert_eq!(Some(String::from("~/src/billing")), config.start_directory);
+        assert_eq!(
+            vec!["make run PORT=9000", "docker ps --format '{{.Names}}'"],
+            config.windows[0].panes[0].commands
+        );
+
+        let mut config = build_config();
+        config
+            .apply_variables_with_env(&BTreeMap::new(), &env)
+            .unwrap();
+        assert_eq!("env", config.name);
+        assert_eq!("make run PORT=8080", config.windows[0].panes[0].commands[0]);
+    }
+
+    #[test]
+    fn it_prefers_the_variables_table_to_unprefixed_environment_variables() {
+        let mut config = Config {
+            variables: BTreeMap::from([
+                (String::from("port"), Value::from(9000)),
+                (String::from("service"), Value::from("table")),
+            ]),
+            ..build_config()
+        };
+        let env = MockEnv(HashMap::from([
+            (String::from("service"), String::from("env")),
+            (String::from("RMUXINATOR_VAR_port"), String::from("9001")),
+        ]));
+        config
+            .apply_variables_with_env(&BTreeMap::new(), &env)
+            .unwrap();
+        assert_eq!("table", config.name);
+        assert_eq!("make run PORT=9001", config.windows[0].panes[0].commands[0]);
+    }
+
+    #[test]
+    fn it_lists_unresolved_variables() {
+        let mut config = build_config();
+        config.windows[0].name = Some(String::from("{{ role }}"));
+        let actual = config.apply_variables_with_env(&BTreeMap::new(), &MockEnv(HashMap::new()));
+        let expected = [
+            "Unresolved template variables:",
+            "- role (used by windows[0].name)",
+            "- service (used by name, start_directory)",
+            "Pass them as arguments (e.g. name=value), set them as environment variables (e.g. RMUXINATOR_VAR_name=value) or give them defaults in the variables table.",
+        ]
+        .join("\n");
+        assert_eq!(Err(expected), actual);
+    }
+
+    #[test]
+    fn it_rejects_non_scalar_variables() {
+        let mut config = Config {
+            variables: BTreeMap::from([(String::from("hosts"), Value::Array(vec![]))]),
+            ..build_config()
+        };
+        assert_eq!(
+            Err(String::from(
+                "variables.hosts must be a string, number or boolean"
+            )),
+            config.apply_variables(&BTreeMap::new())
+        );
+    }
+}
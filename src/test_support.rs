@@ -0,0 +1,22 @@
+use crate::EnvProvider;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// Helpers which are shared by the tests of several modules.
+
+pub struct MockEnv(pub HashMap<String, String>);
+
+impl EnvProvider for MockEnv {
+    fn get_var(&self, key: &str) -> Option<String> {
+        self.0.get(key).cloned()
+    }
+}
+
+/// Write a file (and any missing parent dirs) to `dir`, returning its path.
+pub fn write_file(dir: &Path, name: &str, contents: &str) -> String {
+    let path = dir.join(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, contents).unwrap();
+    path.display().to_string()
+}
@@ -0,0 +1,409 @@
+use crate::{is_screen, Config, Layout, ScreenBackend};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Severity {
+    /// The project can't be started as configured
+    Error,
+    /// The project can be started, but probably not as intended
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "Error"),
+            Severity::Warning => write!(f, "Warning"),
+        }
+    }
+}
+
+/// A problem with a project config, addressed by the path to the offending
+/// value (e.g. `windows[2].panes[1]`).
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub path: String,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn error(path: &str, message: String) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Error,
+            path: String::from(path),
+            message,
+        }
+    }
+
+    fn warning(path: &str, message: String) -> Diagnostic {
+        Diagnostic {
+            severity: Severity::Warning,
+            path: String::from(path),
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// The number of panes described by a custom (i.e. `list-windows`) layout or
+/// None if it isn't one.
+fn count_layout_panes(layout: &Layout) -> Option<usize> {
+    match layout {
+        Layout::Custom(value) => {
+            // Only leaf cells (i.e. panes) end with a pane ID, e.g. the two
+            // panes in 5b5e,274x62,0,0{137x62,0,0,1,136x62,138,0,2}
+            static PANE_RE: OnceLock<Regex> = OnceLock::new();
+            let pane_re = PANE_RE.get_or_init(|| Regex::new(r"\d+x\d+,\d+,\d+,\d+").unwrap());
+            match pane_re.find_iter(value).count() {
+                0 => None,
+                count => Some(count),
+            }
+        }
+        _ => None,
+    }
+}
+
+fn check_layout_pane_count(
+    diagnostics: &mut Vec<Diagnostic>,
+    path: &str,
+    description: &str,
+    layout: &Layout,
+    window_pane_count: usize,
+) {
+    if let Some(layout_pane_count) = count_layout_panes(layout) {
+        if layout_pane_count != window_pane_count {
+            diagnostics.push(Diagnostic::error(
+                path,
+                format!(
+                    "{} has {} panes but the window has {}",
+                    description, layout_pane_count, window_pane_count
+                ),
+            ));
+        }
+    }
+}
+
+// tmux uses these to separate the session from the window and the window
+// from the pane in targets.
+fn has_target_separator(name: &str) -> bool {
+    name.contains(':') || name.contains('.')
+}
+
+impl Config {
+    /// Check for problems which can't be caught when the config is parsed
+    /// (e.g. duplicate window names). An empty result means the config is
+    /// valid.
+    ///
+    /// NOTE: Custom layouts are only checked once references to named
+    /// layouts have been resolved (see `resolve_named_layouts`).
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+
+        if self.name.is_empty() {
+            diagnostics.push(Diagnostic::error(
+                "name",
+                String::from("session name must not be empty"),
+            ));
+        }
+        if has_target_separator(&self.name) {
+            diagnostics.push(Diagnostic::error(
+                "name",
+                format!("session name \"{}\" must not contain ':' or '.'", self.name),
+            ));
+        }
+        if self.terminal_multiplexer.is_empty() {
+            diagnostics.push(Diagnostic::error(
+                "terminal_multiplexer",
+                String::from("terminal_multiplexer must not be empty"),
+            ));
+        }
+
+        let mut window_names: HashMap<&str, usize> = HashMap::new();
+        for (window_index, window) in self.windows.iter().enumerate() {
+            let window_path = format!("windows[{}]", window_index);
+
+            if let Some(name) = &window.name {
+                if has_target_separator(name) {
+                    diagnostics.push(Diagnostic::error(
+                        &format!("{}.name", window_path),
+                        format!("window name \"{}\" must not contain ':' or '.'", name),
+                    ));
+                }
+                match window_names.get(name.as_str()) {
+                    Some(first_window_index) => diagnostics.push(Diagnostic::warning(
+                        &format!("{}.name", window_path),
+                        format!(
+                            "duplicate window name \"{}\" (also used by windows[{}])",
+                            name, first_window_index
+                        ),
+                    )),
+                    None => {
+                        window_names.insert(name, window_index);
+                    }
+                }
+            }
+
+            // Every window has at least one pane.
+            let window_pane_count = window.panes.len().max(1);
+            match &window.layout {
+                Some(layout) => check_layout_pane_count(
+                    &mut diagnostics,
+                    &format!("{}.layout", window_path),
+                    "custom layout",
+                    layout,
+                    window_pane_count,
+                ),
+                None => {
+                    if let Some(layout) = &self.layout {
+                        check_layout_pane_count(
+                            &mut diagnostics,
+                            &window_path,
+                            "the project's custom layout",
+                            layout,
+                            window_pane_count,
+                        )
+                    }
+                }
+            }
+            for (responsive_layout_index, responsive_layout) in
+                window.responsive_layouts.iter().enumerate()
+            {
+                check_layout_pane_count(
+                    &mut diagnostics,
+                    &format!(
+                        "{}.responsive_layouts[{}].layout",
+                        window_path, responsive_layout_index
+                    ),
+                    "custom layout",
+                    &responsive_layout.layout,
+                    window_pane_count,
+                );
+            }
+
+            for (pane_index, pane) in window.panes.iter().enumerate() {
+                let pane_path = format!("{}.panes[{}]", window_path, pane_index);
+
+                if pane.commands.is_empty() && pane.name.is_none() {
+                    diagnostics.push(Diagnostic::warning(
+                        &pane_path,
+                        String::from("pane has neither commands nor a name"),
+                    ));
+                }
+                if pane.name.is_some() && self.pane_name_user_option.is_none() {
+                    diagnostics.push(Diagnostic::warning(
+                        &format!("{}.name", pane_path),
+                        String::from(
+                            "pane name is ignored because pane_name_user_option isn't set",
+                        ),
+                    ));
+                }
+            }
+        }
+
+        if is_screen(&self.terminal_multiplexer) {
+            for path in ScreenBackend::unsupported_layouts(self) {
+                diagnostics.push(Diagnostic::error(
+                    &path,
+                    String::from("layouts can't be used with screen, which can't arrange panes"),
+                ));
+            }
+        }
+
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Hook, HookName, Pane, ResponsiveLayout, Window};
+
+    fn build_pane(command: &str) -> Pane {
+        Pane {
+            commands: vec![String::from(command)],
+            ..Pane::default()
+        }
+    }
+
+    fn build_config() -> Config {
+        Config {
+            name: String::from("foo"),
+            terminal_multiplexer: String::from("tmux"),
+            ..Config::default()
+        }
+    }
+
+    fn describe(diagnostics: Vec<Diagnostic>) -> Vec<String> {
+        diagnostics
+            .iter()
+            .map(|diagnostic| format!("{}: {}", diagnostic.severity, diagnostic))
+            .collect()
+    }
+
+    #[test]
+    fn it_accepts_a_valid_config() {
+        let config = Config {
+            hooks: vec![Hook::new(HookName::ClientAttached, "run-shell 'echo hi'")],
+            windows: vec![Window {
+                name: Some(String::from("editor")),
+                panes: vec![build_pane("vim")],
+                ..Window::default()
+            }],
+            ..build_config()
+        };
+        assert!(config.validate().is_empty());
+    }
+
+    #[test]
+    fn it_rejects_invalid_session_names() {
+        let config = Config {
+            name: String::from(""),
+            ..build_config()
+        };
+        assert_eq!(
+            vec!["Error: name: session name must not be empty"],
+            describe(config.validate())
+        );
+
+        let config = Config {
+            name: String::from("foo.bar"),
+            ..build_config()
+        };
+        assert_eq!(
+            vec!["Error: name: session name \"foo.bar\" must not contain ':' or '.'"],
+            describe(config.validate())
+        );
+    }
+
+    #[test]
+    fn it_warns_about_duplicate_window_names() {
+        let build_window = |name: &str| Window {
+            name: Some(String::from(name)),
+            panes: vec![build_pane("top")],
+            ..Window::default()
+        };
+        let config = Config {
+            windows: vec![
+                build_window("logs"),
+                build_window("editor"),
+                build_window("logs"),
+            ],
+            ..build_config()
+        };
+        assert_eq!(
+            vec!["Warning: windows[2].name: duplicate window name \"logs\" (also used by windows[0])"],
+            describe(config.validate())
+        );
+    }
+
+    #[test]
+    fn it_warns_about_empty_and_unnamed_panes() {
+        let config = Config {
+            windows: vec![Window {
+                panes: vec![
+                    build_pane("vim"),
+                    Pane::default(),
+                    Pane {
+                        name: Some(String::from("shell")),
+                        ..Pane::default()
+                    },
+                ],
+                ..Window::default()
+            }],
+            ..build_config()
+        };
+        assert_eq!(
+            vec![
+                "Warning: windows[0].panes[1]: pane has neither commands nor a name",
+                "Warning: windows[0].panes[2].name: pane name is ignored because pane_name_user_option isn't set",
+            ],
+            describe(config.validate())
+        );
+    }
+
+    #[test]
+    fn it_rejects_custom_layouts_with_the_wrong_number_of_panes() {
+        let two_pane_layout =
+            Layout::Custom(String::from("5b5e,274x62,0,0{137x62,0,0,1,136x62,138,0,2}"));
+        let three_pane_layout = Layout::Custom(String::from(
+            "9959,274x62,0,0{137x62,0,0,1,136x62,138,0[136x31,138,0,2,136x30,138,32,3]}",
+        ));
+        let config = Config {
+            layout: Some(two_pane_layout.clone()),
+            windows: vec![
+                Window {
+                    panes: vec![build_pane("vim"), build_pane("top")],
+                    ..Window::default()
+                },
+                Window {
+                    layout: Some(three_pane_layout),
+                    panes: vec![build_pane("vim"), build_pane("top")],
+                    responsive_layouts: vec![ResponsiveLayout {
+                        layout: two_pane_layout,
+                        max_height: None,
+                        max_width: Some(80),
+                        min_height: None,
+                        min_width: None,
+                    }],
+                    ..Window::default()
+                },
+                Window {
+                    panes: vec![build_pane("vim")],
+                    ..Window::default()
+                },
+            ],
+            ..build_config()
+        };
+        assert_eq!(
+            vec![
+                "Error: windows[1].layout: custom layout has 3 panes but the window has 2",
+                "Error: windows[2]: the project's custom layout has 2 panes but the window has 1",
+            ],
+            describe(config.validate())
+        );
+    }
+
+    #[test]
+    fn it_rejects_layouts_with_screen() {
+        let config = Config {
+            layout: Some(Layout::Tiled),
+            terminal_multiplexer: String::from("/usr/bin/screen"),
+            windows: vec![Window {
+                panes: vec![build_pane("top")],
+                ..Window::default()
+            }],
+            ..build_config()
+        };
+        assert_eq!(
+            vec!["Error: layout: layouts can't be used with screen, which can't arrange panes"],
+            describe(config.validate())
+        );
+    }
+
+    #[test]
+    fn it_rejects_invalid_window_names() {
+        let config = Config {
+            windows: vec![Window {
+                name: Some(String::from("api:8080")),
+                panes: vec![build_pane("top")],
+                ..Window::default()
+            }],
+            ..build_config()
+        };
+        assert_eq!(
+            vec!["Error: windows[0].name: window name \"api:8080\" must not contain ':' or '.'"],
+            describe(config.validate())
+        );
+    }
+}
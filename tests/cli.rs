@@ -50,7 +50,7 @@ tmux_options = "{}"
     );
     writeln!(config_file, "{}", file_contents)?;
 
-    let expected = vec![
+    let expected = [
         format!(
             "tmux {} new-session -d -s debug -n one",
             temp_tmux_config_file_flag
@@ -86,10 +86,14 @@ FLAGS:
     -V, --version    Prints version information
 
 SUBCOMMANDS:
-    debug    Print the tmux commands that would be used to start and configure a tmux session using a path to a
-             project config file
-    help     Prints this message or the help of the given subcommand(s)
-    start    Start a tmux session using a path to a project config file"#,
+    debug       Print the tmux commands that would be used to start and configure a tmux session using a path to a
+                project config file
+    export      Convert a project config file into a format which can be used without rmuxinator
+    freeze      Capture a running tmux session as a project config file
+    help        Prints this message or the help of the given subcommand(s)
+    import      Convert another tool's project file into a project config file
+    start       Start a tmux session using a path to a project config file
+    validate    Check a project config file for problems (e.g. duplicate window names)"#,
         env!("CARGO_PKG_NAME"),
         env!("CARGO_PKG_VERSION"),
         env!("CARGO_PKG_AUTHORS"),
@@ -134,7 +138,7 @@ fn missing_project() -> Result<(), Box<dyn std::error::Error>> {
     <PROJECT_CONFIG_FILE>
 
 USAGE:
-    {} start <PROJECT_CONFIG_FILE>
+    {} start [OPTIONS] <PROJECT_CONFIG_FILE> [VARIABLES]...
 
 For more information try --help"#,
         env!("CARGO_PKG_NAME")
@@ -181,9 +185,13 @@ fn invalid_toml() -> Result<(), Box<dyn std::error::Error>> {
 
     let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
     cmd.arg("start").arg(file.path());
-    cmd.assert().failure().stderr(predicate::str::contains(
-        "Problem parsing config file: expected an equals, found an identifier at line 1",
-    ));
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "Problem parsing config file: {}:1:6: expected an equals, found an identifier",
+            file.path().display()
+        )))
+        .stderr(predicate::str::contains("1 | Toml ain't Yaml\n  |      ^"));
 
     Ok(())
 }
@@ -201,9 +209,477 @@ fn invalid_project_toml() -> Result<(), Box<dyn std::error::Error>> {
 
     let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
     cmd.arg("start").arg(file.path());
-    cmd.assert().failure().stderr(predicate::str::contains(
-        "Problem parsing config file: missing field `name`",
-    ));
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "Problem parsing config file: {}:1:1: unknown field `xname`",
+            file.path().display()
+        )))
+        .stderr(predicate::str::contains("= help: did you mean `name`?"));
+
+    Ok(())
+}
+
+#[test]
+fn import_tmuxinator_project() -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = NamedTempFile::new()?;
+    writeln!(
+        file,
+        r#"
+name: sample
+root: ~/<%= @settings["project"] %>
+windows:
+  - editor: vim
+"#
+    )?;
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME"))?;
+    cmd.arg("import").arg("tmuxinator").arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#"[[windows]]
+name = "editor"
+
+[[windows.panes]]
+commands = ["vim"]"#,
+        ))
+        .stderr(predicate::str::contains(
+            "Warning: ERB tags are not evaluated and have been imported verbatim",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn validate_project() -> Result<(), Box<dyn std::error::Error>> {
+    let mut config_file = NamedTempFile::new()?;
+    let file_contents = r#"
+name = "validate.me"
+[[windows]]
+  name = "logs"
+  [[windows.panes]]
+    commands = ["tail -f log"]
+[[windows]]
+  name = "logs"
+  [[windows.panes]]
+    commands = []
+    "#;
+    writeln!(config_file, "{}", file_contents)?;
+
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+        .arg("lint")
+        .arg(config_file.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Error: name: session name \"validate.me\" must not contain ':' or '.'",
+        ))
+        .stderr(predicate::str::contains(
+            "Warning: windows[1].name: duplicate window name \"logs\" (also used by windows[0])",
+        ))
+        .stderr(predicate::str::contains(
+            "Warning: windows[1].panes[0]: pane has neither commands nor a name",
+        ))
+        .stderr(predicate::str::contains("Found 1 error"));
+
+    Ok(())
+}
+
+#[test]
+fn validate_project_with_conditional_windows() -> Result<(), Box<dyn std::error::Error>> {
+    let mut config_file = NamedTempFile::new()?;
+    let file_contents = r#"
+name = "foo"
+[[windows]]
+  name = "logs"
+  when = { os = "not-an-os" }
+  [[windows.panes]]
+    commands = ["tail -f log"]
+[[windows]]
+  name = "logs"
+  [[windows.panes]]
+    commands = ["journalctl -f"]
+    "#;
+    writeln!(config_file, "{}", file_contents)?;
+
+    // Windows are checked whether or not their conditions hold.
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+        .arg("validate")
+        .arg(config_file.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Warning: windows[1].name: duplicate window name \"logs\" (also used by windows[0])",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn validate_yaml_project() -> Result<(), Box<dyn std::error::Error>> {
+    let mut config_file = tempfile::Builder::new().suffix(".yml").tempfile()?;
+    writeln!(
+        config_file,
+        r#"
+name: yaml
+windows:
+  - name: editor
+    panes:
+      - commands: [vim]
+"#
+    )?;
+
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+        .arg("validate")
+        .arg(config_file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("yaml is valid."));
+
+    Ok(())
+}
+
+#[test]
+fn export_project_as_yaml() -> Result<(), Box<dyn std::error::Error>> {
+    let mut config_file = NamedTempFile::new()?;
+    writeln!(
+        config_file,
+        r#"
+name = "toml"
+[[windows]]
+  layout = "tiled"
+  [[windows.panes]]
+    commands = ["vim"]
+"#
+    )?;
+
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+        .arg("export")
+        .arg("--format")
+        .arg("yaml")
+        .arg(config_file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#"name: toml
+windows:
+- layout: tiled
+  panes:
+  - commands:
+    - vim
+"#,
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn export_project_as_a_shell_script_without_tmux() -> Result<(), Box<dyn std::error::Error>> {
+    let mut config_file = NamedTempFile::new()?;
+    writeln!(
+        config_file,
+        r#"
+name = "shell"
+[[windows]]
+  [[windows.panes]]
+    commands = ["vim"]
+"#
+    )?;
+
+    // The script looks up the base-index and pane-base-index when it's run,
+    // so generating it doesn't need tmux.
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+        .arg("export")
+        .arg("--format")
+        .arg("sh")
+        .arg(config_file.path())
+        .env("PATH", "")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("#!/bin/sh"))
+        .stdout(predicate::str::contains(
+            "tmux send-keys -t shell:\"$((base_index + 0)).$((pane_base_index + 0))\" vim Enter",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn export_project_as_a_zellij_layout_without_tmux() -> Result<(), Box<dyn std::error::Error>> {
+    let mut config_file = NamedTempFile::new()?;
+    writeln!(
+        config_file,
+        r#"
+name = "zellij"
+[[windows]]
+  name = "editor"
+  [[windows.panes]]
+    commands = ["vim"]
+"#
+    )?;
+
+    // Exporting doesn't run tmux, so it works on machines without it.
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+        .arg("export")
+        .arg("--format")
+        .arg("zellij")
+        .arg(config_file.path())
+        .env("PATH", "")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("tab name=\"editor\""));
+
+    Ok(())
+}
+
+#[test]
+fn validate_project_from_stdin() -> Result<(), Box<dyn std::error::Error>> {
+    assert_cmd::Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+        .arg("validate")
+        .arg("--config-format")
+        .arg("json")
+        .arg("-")
+        .write_stdin(r#"{"name": "stdin", "windows": [{"panes": [{"commands": ["vim"]}]}]}"#)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("stdin is valid."));
+
+    assert_cmd::Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+        .arg("validate")
+        .arg("--config-format")
+        .arg("toml")
+        .arg("-")
+        .write_stdin("name: stdin")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Problem parsing config file: <stdin>:1:5: expected an equals, found a colon",
+        ));
+
+    assert_cmd::Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+        .arg("validate")
+        .arg("-")
+        .arg("--format")
+        .arg("yaml")
+        .write_stdin("name: stdin")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("stdin is valid."));
+
+    assert_cmd::Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+        .arg("validate")
+        .arg("-")
+        .write_stdin("name = \"stdin\"")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--config-format <CONFIG_FORMAT>"));
+
+    Ok(())
+}
+
+#[test]
+fn validate_project_with_template_variables() -> Result<(), Box<dyn std::error::Error>> {
+    let mut config_file = NamedTempFile::new()?;
+    writeln!(
+        config_file,
+        r#"
+name = "{{{{ service }}}}"
+[variables]
+  port = 8080
+[[windows]]
+  name = "{{{{ role }}}}"
+  [[windows.panes]]
+    commands = ["make run PORT={{{{ port }}}}"]
+"#
+    )?;
+
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+        .arg("validate")
+        .arg(config_file.path())
+        .arg("service=billing")
+        .arg("role=api")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("billing is valid."));
+
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+        .arg("validate")
+        .arg(config_file.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Unresolved template variables:\n- role (used by windows[0].name)\n- service (used by name)",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn debug_project_which_extends_a_base_config() -> Result<(), Box<dyn std::error::Error>> {
+    let project_dir = tempfile::TempDir::new()?;
+    std::fs::write(
+        project_dir.path().join("base.toml"),
+        r#"
+tmux_options = "-L base"
+[[windows]]
+  name = "logs"
+  [[windows.panes]]
+    commands = ["tail -f log"]
+"#,
+    )?;
+    let project_path = project_dir.path().join("project.toml");
+    std::fs::write(
+        &project_path,
+        r#"
+extends = "base.toml"
+name = "foo"
+[[windows]]
+  name = "editor"
+  [[windows.panes]]
+    commands = ["vim"]
+"#,
+    )?;
+
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+        .arg("debug")
+        .arg("--format")
+        .arg("config")
+        .arg(&project_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("tmux_options = \"-L base\""))
+        .stdout(predicate::str::contains("name = \"logs\""))
+        .stdout(predicate::str::contains("name = \"editor\""))
+        .stdout(predicate::str::contains("extends").not());
+
+    Ok(())
+}
+
+#[test]
+fn debug_project_with_a_profile() -> Result<(), Box<dyn std::error::Error>> {
+    let mut config_file = NamedTempFile::new()?;
+    writeln!(
+        config_file,
+        r#"
+name = "foo"
+[[windows]]
+  name = "vpn"
+  [[windows.panes]]
+    commands = ["sudo openvpn home.ovpn"]
+[[windows]]
+  name = "editor"
+  [[windows.panes]]
+    commands = ["vim"]
+[profiles.work]
+  remove_windows = ["vpn"]
+"#
+    )?;
+
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+        .arg("debug")
+        .arg("--format")
+        .arg("config")
+        .arg("--profile")
+        .arg("work")
+        .arg(config_file.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Using profile \"work\""))
+        .stdout(predicate::str::contains("name = \"editor\""))
+        .stdout(predicate::str::contains("vpn").not());
+
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+        .arg("validate")
+        .arg("--profile")
+        .arg("home")
+        .arg(config_file.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Unknown profile \"home\" (available profiles: work)",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn debug_project_with_conditional_windows() -> Result<(), Box<dyn std::error::Error>> {
+    let mut config_file = NamedTempFile::new()?;
+    writeln!(
+        config_file,
+        r#"
+name = "foo"
+[[windows]]
+  name = "docker"
+  when = {{ env = "RMUXINATOR_HAS_DOCKER" }}
+  [[windows.panes]]
+    commands = ["docker compose logs -f"]
+[[windows]]
+  name = "editor"
+  [[windows.panes]]
+    commands = ["vim"]
+"#
+    )?;
+
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+        .arg("debug")
+        .arg(config_file.path())
+        .env_remove("RMUXINATOR_HAS_DOCKER")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("new-session -d -s foo -n editor"))
+        .stdout(predicate::str::contains("docker").not());
+
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+        .arg("debug")
+        .arg(config_file.path())
+        .env("RMUXINATOR_HAS_DOCKER", "1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("docker compose logs -f"));
+
+    // Conditions are kept in the project config itself.
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+        .arg("debug")
+        .arg("--format")
+        .arg("config")
+        .arg(config_file.path())
+        .env_remove("RMUXINATOR_HAS_DOCKER")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("env = \"RMUXINATOR_HAS_DOCKER\""));
+
+    Ok(())
+}
+
+#[test]
+fn debug_project_with_repeated_windows() -> Result<(), Box<dyn std::error::Error>> {
+    let mut config_file = NamedTempFile::new()?;
+    writeln!(
+        config_file,
+        r#"
+name = "foo"
+[[windows]]
+  name = "{{{{ item }}}}"
+  for_each = ["api", "web"]
+  [[windows.panes]]
+    commands = ["make run PORT={{{{ port }}}}{{{{ index }}}}"]
+"#
+    )?;
+
+    Command::cargo_bin(env!("CARGO_PKG_NAME"))?
+        .arg("debug")
+        .arg("--format")
+        .arg("config")
+        .arg(config_file.path())
+        .arg("port=800")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("name = \"api\""))
+        .stdout(predicate::str::contains("make run PORT=8000"))
+        .stdout(predicate::str::contains("name = \"web\""))
+        .stdout(predicate::str::contains("make run PORT=8001"));
 
     Ok(())
 }